@@ -1,4 +1,4 @@
-use anyhow::ensure;
+use anyhow::{anyhow, bail, ensure};
 use serde_derive::{Deserialize, Serialize};
 
 pub mod template;
@@ -40,16 +40,13 @@ impl ProjectsClient {
         access_token: &str,
         script_id: &str,
         source: String,
+        manifest: &Manifest,
     ) -> anyhow::Result<Content> {
         let url = format!(
             "https://script.googleapis.com/v1/projects/{}/content?access_token={}",
             script_id, access_token
         );
 
-        let manifest_source =
-            r#"{"timeZone":"Europe/Berlin","dependencies":{},"exceptionLogging":"STACKDRIVER"}"#
-                .to_owned();
-
         let mut resp: reqwest::Response = self
             ._http_client
             .put(url.as_str())
@@ -65,7 +62,7 @@ impl ProjectsClient {
                     File {
                         name: "appsscript".to_owned(),
                         file_type: FileType::Json,
-                        source: manifest_source,
+                        source: serde_json::to_string(manifest)?,
                         ..Default::default()
                     },
                 ],
@@ -75,6 +72,91 @@ impl ProjectsClient {
         ensure!(resp.status().is_success(), resp.text()?);
         Ok(resp.json::<Content>()?)
     }
+
+    // Creates a new immutable version using the current code, with a unique version number.
+    pub fn create_version(
+        &self,
+        access_token: &str,
+        script_id: &str,
+        description: String,
+    ) -> anyhow::Result<Version> {
+        let url = format!(
+            "https://script.googleapis.com/v1/projects/{}/versions?access_token={}",
+            script_id, access_token
+        );
+
+        let mut resp: reqwest::Response = self
+            ._http_client
+            .post(url.as_str())
+            .body(serde_json::to_vec(&Version {
+                description: Some(description),
+                ..Default::default()
+            })?)
+            .send()?;
+
+        ensure!(resp.status().is_success(), resp.text()?);
+        Ok(resp.json::<Version>()?)
+    }
+
+    // Creates a deployment of an Apps Script project.
+    pub fn create_deployment(
+        &self,
+        access_token: &str,
+        config: &DeploymentConfig,
+    ) -> anyhow::Result<Deployment> {
+        let url = format!(
+            "https://script.googleapis.com/v1/projects/{}/deployments?access_token={}",
+            config.script_id, access_token
+        );
+
+        let mut resp: reqwest::Response = self
+            ._http_client
+            .post(url.as_str())
+            .body(serde_json::to_vec(config)?)
+            .send()?;
+
+        ensure!(resp.status().is_success(), resp.text()?);
+        Ok(resp.json::<Deployment>()?)
+    }
+
+    // Runs a function in an Apps Script project.
+    // The script must be deployed for use with the Apps Script API
+    // and must be linked to the same Cloud Platform project as the calling application.
+    pub fn run(
+        &self,
+        access_token: &str,
+        deployment_id: &str,
+        request: &ExecutionRequest,
+    ) -> anyhow::Result<serde_json::Value> {
+        let url = format!(
+            "https://script.googleapis.com/v1/scripts/{}:run?access_token={}",
+            deployment_id, access_token
+        );
+
+        let mut resp: reqwest::Response = self
+            ._http_client
+            .post(url.as_str())
+            .body(serde_json::to_vec(request)?)
+            .send()?;
+
+        ensure!(resp.status().is_success(), resp.text()?);
+        let operation = resp.json::<Operation>()?;
+
+        // a script error is reported with a successful status
+        if let Some(status) = operation.error {
+            bail!(
+                "{} `{}`: {}",
+                status.message,
+                request.function,
+                status.describe()
+            );
+        }
+
+        operation
+            .response
+            .map(|response| response.result)
+            .ok_or_else(|| anyhow!("{}: the execution returned no response", request.function))
+    }
 }
 
 #[derive(Deserialize, Serialize, Default)]
@@ -196,8 +278,28 @@ impl Default for FileType {
 
 #[derive(Deserialize, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
-// TODO: implememt
-pub struct FunctionSet {}
+pub struct FunctionSet {
+    // A list of functions composing the set.
+    #[serde(default)]
+    pub values: Vec<Function>,
+}
+
+impl FunctionSet {
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.iter().any(|function| function.name == name)
+    }
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Function {
+    // The function name in the script project.
+    pub name: String,
+
+    // The ordered list of parameter names of the function in the script project.
+    #[serde(default)]
+    pub parameters: Vec<String>,
+}
 
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -210,3 +312,231 @@ pub struct Content {
     // must have type of JSON, and include the manifest configurations for the project.
     pub files: Vec<File>,
 }
+
+impl Content {
+    // Whether any of the script files defines the function
+    pub fn defines(&self, function: &str) -> bool {
+        self.files
+            .iter()
+            .any(|file| matches!(file.function_set, Some(ref set) if set.contains(function)))
+    }
+}
+
+// https://developers.google.com/apps-script/manifest
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    // The script time zone in one of the available ZoneId values such as "America/Denver".
+    pub time_zone: String,
+
+    // The configuration of enabled advanced services and libraries, none are used.
+    pub dependencies: Dependencies,
+
+    // The location where exceptions are logged.
+    pub exception_logging: ExceptionLogging,
+
+    // The script project's API executable configuration.
+    pub execution_api: ExecutionApi,
+}
+
+impl Manifest {
+    // The execution API entry point allows `run` by the owner of the project
+    pub fn new(time_zone: String, exception_logging: ExceptionLogging) -> Self {
+        Manifest {
+            time_zone,
+            dependencies: Dependencies {},
+            exception_logging,
+            execution_api: ExecutionApi {
+                access: "MYSELF".to_owned(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Dependencies {}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExceptionLogging {
+    // Exceptions are not logged.
+    None,
+
+    // Exceptions are logged in Cloud Logging.
+    #[default]
+    Stackdriver,
+}
+
+#[derive(Serialize)]
+pub struct ExecutionApi {
+    // Determines who has permission to run the script from the API.
+    pub access: String,
+}
+
+// A snapshot of the script project code, required by a deployment
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    // The script project's Drive ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_id: Option<String>,
+
+    // The incremental ID that is created by Apps Script when a version is created.
+    // This is system assigned number and is immutable once created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_number: Option<u64>,
+
+    // The description for this version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    // When the version was created.
+    // A timestamp in RFC3339 UTC "Zulu" format, accurate to nanoseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentConfig {
+    // The script project's Drive ID.
+    pub script_id: String,
+
+    // The version number on which this deployment is based.
+    pub version_number: u64,
+
+    // The manifest file name for this deployment.
+    pub manifest_file_name: String,
+
+    // The description for this deployment.
+    pub description: String,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    // The deployment ID for this deployment.
+    pub deployment_id: String,
+
+    // The deployment configuration.
+    pub deployment_config: DeploymentConfig,
+
+    // Last modified date time stamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<String>,
+
+    // The deployment's entry points.
+    #[serde(default)]
+    pub entry_points: Vec<EntryPoint>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPoint {
+    // The type of the entry point, i.e. `EXECUTION_API` or `WEB_APP`.
+    pub entry_point_type: String,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionRequest {
+    // The name of the function to execute in the given script.
+    pub function: String,
+
+    // The parameters to be passed to the function being executed.
+    // The object type for each parameter should match the expected type in Apps Script.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<serde_json::Value>,
+
+    // If true and the user is an owner of the script, the script runs at the most recently saved version
+    // rather than the version deployed for use with the Apps Script API.
+    pub dev_mode: bool,
+}
+
+// The result of `run`: either the `response` or the `error` is set
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    // This field indicates whether the script execution has completed.
+    #[serde(default)]
+    pub done: bool,
+
+    // If the script function returns successfully, this field contains an ExecutionResponse
+    // object with the function's return value.
+    pub response: Option<ExecutionResponse>,
+
+    // If a run call succeeds but the script function (or Apps Script itself) throws an exception,
+    // this field contains a Status object.
+    pub error: Option<Status>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionResponse {
+    // The return value of the script function.
+    #[serde(default)]
+    pub result: serde_json::Value,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    // The status code. For this API, this value either:
+    // 10, indicating a SCRIPT_TIMEOUT error,
+    // 3, indicating an INVALID_ARGUMENT error, or
+    // 1, indicating a CANCELLED execution.
+    pub code: i32,
+
+    // A developer-facing error message, which is in English.
+    pub message: String,
+
+    // An array that contains a single ExecutionError object that provides information about the nature of the error.
+    #[serde(default)]
+    pub details: Vec<ExecutionError>,
+}
+
+impl Status {
+    fn describe(&self) -> String {
+        self.details
+            .iter()
+            .map(|details| {
+                let trace = details
+                    .script_stack_trace_elements
+                    .iter()
+                    .map(|element| format!("{}:{}", element.function, element.line_number))
+                    .collect::<Vec<String>>();
+                format!("{} ({})", details.error_message, trace.join(" < "))
+            })
+            .collect::<Vec<String>>()
+            .join("; ")
+    }
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionError {
+    // The error message thrown by Apps Script, usually localized into the user's language.
+    #[serde(default)]
+    pub error_message: String,
+
+    // The error type, for example TypeError or ReferenceError.
+    #[serde(default)]
+    pub error_type: String,
+
+    // An array of objects that provide a stack trace through the script to show where the execution failed,
+    // with the deepest call first.
+    #[serde(default)]
+    pub script_stack_trace_elements: Vec<ScriptStackTraceElement>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptStackTraceElement {
+    // The name of the function that failed.
+    #[serde(default)]
+    pub function: String,
+
+    // The line number where the script failed.
+    #[serde(default)]
+    pub line_number: i64,
+}
@@ -1,3 +1,13 @@
+use serde_derive::Serialize;
+
+use crate::config::{FormConfig, QuestionConfig, ResponseKind};
+
+// Prefix of the script line holding the serialized template
+const CONFIG_DECLARATION: &str = "var config = ";
+
+// The data of the generated form, embedded into the script as a JSON literal
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Template<'a> {
     assessment_kind: &'a str,
     first_name: String,
@@ -5,8 +15,80 @@ pub struct Template<'a> {
     occasion: String,
     dir_id: String,
     description: String,
-    graded_questions: Vec<String>,
-    text_questions: Vec<String>,
+    anonymous: bool,
+    // shuffles the questions of every section for every respondent
+    shuffle_questions: bool,
+    confirmation_message: Option<String>,
+    allowed_domain: Option<String>,
+    sections: Vec<Section>,
+}
+
+// A page of the form
+#[derive(Serialize)]
+pub struct Section {
+    title: String,
+    help: String,
+    questions: Vec<Question>,
+}
+
+impl Section {
+    // The bounds of the graded questions are expected to be valid form scales
+    pub fn new(title: &str, help: &str, questions: &[&QuestionConfig]) -> Self {
+        Section {
+            title: title.to_owned(),
+            help: help.to_owned(),
+            questions: questions
+                .iter()
+                .map(|question| Question::new(question))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Question {
+    title: String,
+    kind: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scale: Option<Scale>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    options: Vec<String>,
+}
+
+impl Question {
+    fn new(question: &QuestionConfig) -> Self {
+        let scale = match question.response_kind {
+            ResponseKind::Grade | ResponseKind::Nps => {
+                let (min, max) = question.scale();
+                let (low_label, high_label) = question.labels();
+                Some(Scale {
+                    min: min as i32,
+                    max: max as i32,
+                    low_label: low_label.to_owned(),
+                    high_label: high_label.to_owned(),
+                })
+            }
+            _ => None,
+        };
+
+        Question {
+            title: question.title(),
+            kind: question.response_kind.to_string(),
+            scale,
+            options: question.options().into_iter().map(String::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Scale {
+    min: i32,
+    max: i32,
+    low_label: String,
+    high_label: String,
 }
 
 impl<'a> Template<'a> {
@@ -18,8 +100,9 @@ impl<'a> Template<'a> {
         occasion: String,
         dir_id: String,
         description: String,
-        graded_questions: Vec<String>,
-        text_questions: Vec<String>,
+        settings: &FormConfig,
+        shuffle_questions: bool,
+        sections: Vec<Section>,
     ) -> Self {
         Template {
             assessment_kind,
@@ -28,88 +111,249 @@ impl<'a> Template<'a> {
             occasion,
             dir_id,
             description,
-            graded_questions,
-            text_questions,
+            anonymous: settings.anonymous,
+            shuffle_questions,
+            confirmation_message: settings.confirmation_message.clone(),
+            allowed_domain: settings.allowed_domain.clone(),
+            sections,
         }
     }
 
-    pub fn code(&self) -> String {
-        let concat_graded_qs = self.graded_questions.join("\",\"");
-        let concat_text_qs = self.text_questions.join("\",\"");
-
-        format!(
-            r###"function createForm() {{
-   // configuration
-   var cPersonName = "{first_name}";
-   var cPersonSurname = "{last_name}";
-   var cOccasion = "{occasion}";
-   var folderId = "{dir_id}";
-  
-   // create & name Form  
-   var auxDate = new Date();
-   var formDate = auxDate.getFullYear().toString() + (auxDate.getMonth() + 1).toString() + auxDate.getDate().toString();
+    pub fn code(&self) -> anyhow::Result<String> {
+        let config = js_literal(&serde_json::to_string(self)?);
+
+        let code = format!(
+            r###"// configuration
+{declaration}{config};
+
+function createForm() {{
+   // a form can only be restricted to the domain of its owner
+   if (config.allowedDomain) {{
+        var owner = Session.getEffectiveUser().getEmail();
+        if (owner.split("@").pop().toLowerCase() !== config.allowedDomain) {{
+            throw new Error("the form can't be restricted to " + config.allowedDomain + ", it is owned by " + owner);
+        }}
+   }}
 
-   var item = "{assessment_kind}: ##name## ##surname## - ##occasion##"
-      .replace("##name##", cPersonName)
-      .replace("##surname##", cPersonSurname)
-      .replace("##occasion##", cOccasion);
+   // create & name Form
+   var item = config.assessmentKind + ": " + config.firstName + " " + config.lastName + " - " + config.occasion;
 
-   var itemDesc = "{description}";
    var form = FormApp.create(item)  
        .setTitle(item)
-       .setDescription(itemDesc)
-       .setCollectEmail(true)
-       .setLimitOneResponsePerUser(true)
-       .setShuffleQuestions(true)
+       .setDescription(config.description)
+       .setCollectEmail(!config.anonymous)
+       .setLimitOneResponsePerUser(!config.anonymous)
+       .setShuffleQuestions(config.shuffleQuestions)
        .setShowLinkToRespondAgain(false)
        .setProgressBar(true);
 
-   // section 1 agree or disagree   
-   var pageTwo = form.addPageBreakItem()
-       .setTitle("Agree or Disagree")
-       .setGoToPage(FormApp.PageNavigationType.CONTINUE)
-       .setHelpText("Agree or disagree with the provided statements. The scale should be interpreted as follows: 1 - strongly disagree, 3 - neutral, 5 - strongly agree.");
-
-  var qs = ["{concat_graded_qs}"];
-      
-   qs.forEach(function (v, i) {{
-     form.addScaleItem()
-       .setTitle(v)  
-       .setBounds(1, 5)
-       .setLabels("disagree","agree")
-       .setRequired(true);
-   }});
-                
-  // section 2 Strenths and Improvements
-  var text_qs = ["{concat_text_qs}"];
-  
-   if (text_qs.length > 0 && text_qs[0] != "") {{
+   if (config.confirmationMessage) {{
+        form.setConfirmationMessage(config.confirmationMessage);
+   }}
+
+   if (config.allowedDomain) {{
+        form.setRequireLogin(true);
+   }}
+
+   // a page per section
+   config.sections.forEach(function (section) {{
         form.addPageBreakItem()
-            .setTitle("Strengths and Improvements")
+            .setTitle(section.title)
             .setGoToPage(FormApp.PageNavigationType.CONTINUE)
-            .setHelpText("You have 2 boxes to add a text or list about your Strengths and Improvements. The text in this section will be shared directly.");  
-  
-        text_qs.forEach(function (v, i) {{
-            form.addParagraphTextItem()
-            .setTitle(v)
-            .setRequired(true);
+            .setHelpText(section.help);
+
+        section.questions.forEach(function (q, i) {{
+            switch (q.kind) {{
+            case "grade":
+            case "nps":
+                form.addScaleItem()
+                    .setTitle(q.title)
+                    .setBounds(q.scale.min, q.scale.max)
+                    .setLabels(q.scale.lowLabel, q.scale.highLabel)
+                    .setRequired(true);
+                break;
+            case "choice":
+                form.addMultipleChoiceItem()
+                    .setTitle(q.title)
+                    .setChoiceValues(q.options)
+                    .setRequired(true);
+                break;
+            case "checkbox":
+                form.addCheckboxItem()
+                    .setTitle(q.title)
+                    .setChoiceValues(q.options)
+                    .setRequired(true);
+                break;
+            case "numeric":
+                form.addTextItem()
+                    .setTitle(q.title)
+                    .setValidation(FormApp.createTextValidation().requireNumber().build())
+                    .setRequired(true);
+                break;
+            case "text":
+                form.addParagraphTextItem()
+                    .setTitle(q.title)
+                    .setRequired(true);
+                break;
+            }}
         }});
-   }}
+   }});
   
    // move to the right folder
    var file = DriveApp.getFileById(form.getId());
-   DriveApp.getFolderById(folderId).addFile(file);
+   DriveApp.getFolderById(config.dirId).addFile(file);
    DriveApp.getRootFolder().removeFile(file); 
+
+   // read by `generate` from the result of the API execution
+   return {{
+     formId: form.getId(),
+     responderUrl: form.getPublishedUrl(),
+     editUrl: form.getEditUrl()
+   }};
 }}
 "###,
-            assessment_kind = self.assessment_kind,
-            first_name = self.first_name,
-            last_name = self.last_name,
-            occasion = self.occasion,
-            dir_id = self.dir_id,
-            description = self.description,
-            concat_graded_qs = concat_graded_qs,
-            concat_text_qs = concat_text_qs,
-        )
+            declaration = CONFIG_DECLARATION,
+            config = config,
+        );
+
+        Ok(code)
+    }
+}
+
+// JSON is valid JavaScript, except for the line and paragraph separators in strings of older engines.
+// `<` is escaped as well, so the script can be embedded into a page as is.
+fn js_literal(json: &str) -> String {
+    json.replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+        .replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use super::*;
+
+    const NASTY: &[&str] = &[
+        "double \" quote",
+        "single ' quote",
+        "back\\slash \\u0041",
+        "new\nline\r\n",
+        "end </script><script>alert(1)</script>",
+        "line\u{2028}separator",
+        "paragraph\u{2029}separator",
+        "\"; maliciousCall(); //",
+        "'); maliciousCall(); //",
+        "${maliciousCall()}",
+    ];
+
+    fn question(text: &str, response_kind: ResponseKind) -> QuestionConfig {
+        QuestionConfig {
+            id: Some(text.to_owned()),
+            assessment_kind: text.to_owned(),
+            sheet: None,
+            response_kind,
+            category: text.to_owned(),
+            template_raw: text.to_owned(),
+            template_final: text.to_owned(),
+            weight: 1.0,
+            scale_min: None,
+            scale_max: None,
+            low_label: Some(text.to_owned()),
+            high_label: Some(text.to_owned()),
+            options: Some(format!("{} | other", text.replace('|', ""))),
+            category_description: Some(text.to_owned()),
+            shuffle: None,
+        }
+    }
+
+    // The JSON literal of the `var config = …;` line
+    fn declared(code: &str) -> &str {
+        code.lines()
+            .find_map(|line| line.strip_prefix(CONFIG_DECLARATION))
+            .and_then(|line| line.strip_suffix(';'))
+            .expect("the configuration is declared on a single line")
+    }
+
+    // Generates the script of a form with the text in every field, the declared configuration
+    // must parse to the template data
+    fn template(text: &str) -> String {
+        let questions = [
+            question(text, ResponseKind::Grade),
+            question(text, ResponseKind::Choice),
+            question(text, ResponseKind::Text),
+        ];
+        let settings = FormConfig {
+            confirmation_message: Some(text.to_owned()),
+            allowed_domain: Some(text.to_owned()),
+            ..FormConfig::default()
+        };
+
+        let template = Template::new(
+            text,
+            text.to_owned(),
+            text.to_owned(),
+            text.to_owned(),
+            text.to_owned(),
+            text.to_owned(),
+            &settings,
+            true,
+            vec![Section::new(
+                text,
+                text,
+                &questions.iter().collect::<Vec<&QuestionConfig>>(),
+            )],
+        );
+
+        let code = template.code().unwrap();
+        let expected = serde_json::to_value(&template).unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_str(declared(&code)).unwrap();
+        assert_eq!(parsed, expected, "{:?}", text);
+
+        code
+    }
+
+    #[test]
+    fn configuration_round_trips() {
+        for text in NASTY {
+            let code = template(text);
+
+            assert!(!code.contains("</script"), "{:?}", text);
+            assert!(!code.contains('\u{2028}') && !code.contains('\u{2029}'));
+        }
+    }
+
+    // Evaluates the script with node and compares the configuration it sees,
+    // run it with `cargo test -- --ignored` where node is installed
+    #[test]
+    #[ignore]
+    fn script_evaluates_to_the_configuration() {
+        for text in NASTY {
+            let code = template(text);
+            let script = format!("{}\nprocess.stdout.write(JSON.stringify(config));\n", code);
+
+            let mut node = Command::new("node")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("node is required to evaluate the generated script");
+            node.stdin
+                .take()
+                .unwrap()
+                .write_all(script.as_bytes())
+                .unwrap();
+            let output = node.wait_with_output().unwrap();
+            assert!(output.status.success(), "{:?}", text);
+
+            let evaluated: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(
+                evaluated,
+                serde_json::from_str::<serde_json::Value>(declared(&code)).unwrap(),
+                "{:?}",
+                text
+            );
+        }
     }
 }
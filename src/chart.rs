@@ -1,18 +1,127 @@
+use anyhow::anyhow;
+
+use crate::drive::{self, SpreadsheetClient};
 use crate::sheets;
 
 use sheets::basic_chart::*;
-use sheets::spreadsheets::{ChartSpec, EmbeddedChart, EmbeddedObjectPosition};
+use sheets::spreadsheets::{ChartSpec, EmbeddedChart, EmbeddedObjectPosition, Spreadsheet};
 use sheets::spreadsheets_batch_update::*;
+use sheets::spreadsheets_values::A1Range;
 
-// https://developers.google.com/sheets/api/samples/charts#add_a_column_chart
-pub fn add_summary_chart(
+// Developer metadata key of the sheet holding the generated chart
+pub const CHART_METADATA_KEY: &str = "esurvey-chart";
+
+// Updates the chart of a previous evaluation in place, or adds a new one on its own sheet.
+// The chart is recognized by the developer metadata of its sheet, or by title.
+pub fn save_summary_chart(
     client: &sheets::Client,
     token: &str,
-    spreadsheet_id: &str,
+    spreadsheet: &Spreadsheet,
     sheet_id: u64,
     title: String,
+    range: A1Range,
+    series_count: u64,
 ) -> anyhow::Result<()> {
-    let chart_spec = ChartSpec {
+    let spreadsheet_id = spreadsheet.spreadsheet_id.as_str();
+    let spec = summary_chart_spec(sheet_id, title.clone(), range, series_count);
+
+    let existing = drive::find_tagged_sheet(spreadsheet, CHART_METADATA_KEY)
+        .and_then(|sheet| sheet.charts.first())
+        .or_else(|| {
+            spreadsheet
+                .sheets
+                .iter()
+                .flat_map(|sheet| sheet.charts.iter())
+                .find(|chart| chart.spec.title.as_deref() == Some(title.as_str()))
+        });
+
+    if let Some(chart) = existing {
+        println!("updating the existing chart: > {}", chart.chart_id);
+        let chart_req = SpreadsheetBatchUpdate {
+            requests: vec![Request {
+                update_chart_spec: Some(UpdateChartSpecRequest {
+                    chart_id: chart.chart_id,
+                    spec,
+                }),
+                ..Default::default()
+            }],
+            response_ranges: Vec::new(),
+            response_include_grid_data: false,
+            include_spreadsheet_in_response: false,
+        };
+
+        client.batch_update_spreadsheet(token, spreadsheet_id, &chart_req)?;
+        return Ok(());
+    }
+
+    let chart_req = SpreadsheetBatchUpdate {
+        requests: vec![Request {
+            add_chart: Some(AddChartRequest {
+                chart: EmbeddedChart {
+                    chart_id: None,
+                    spec,
+                    position: EmbeddedObjectPosition {
+                        new_sheet: true,
+                        ..Default::default()
+                    },
+                },
+            }),
+            ..Default::default()
+        }],
+        response_ranges: Vec::new(),
+        response_include_grid_data: false,
+        include_spreadsheet_in_response: false,
+    };
+
+    let response_body = client.batch_update_spreadsheet(token, spreadsheet_id, &chart_req)?;
+    let chart_sheet_id = response_body
+        .replies
+        .first()
+        .and_then(|reply| reply.add_chart.as_ref())
+        .and_then(|reply| reply.chart.position.sheet_id)
+        .ok_or_else(|| anyhow!("save_summary_chart: chart sheet_id not available"))?;
+
+    SpreadsheetClient::new(client, token).add_metadata(
+        spreadsheet_id,
+        chart_sheet_id,
+        CHART_METADATA_KEY,
+    )
+}
+
+// https://developers.google.com/sheets/api/samples/charts#add_a_column_chart
+//
+// `range` is the written table: its first row is the header with the categories,
+// every of the following `series_count` rows is drawn as a separate series
+fn summary_chart_spec(
+    sheet_id: u64,
+    title: String,
+    range: A1Range,
+    series_count: u64,
+) -> ChartSpec {
+    let start_row = range.start_row;
+    let series_count = series_count.min((range.end_row - range.start_row).saturating_sub(1));
+
+    let series = (1..=series_count)
+        .map(|offset| BasicChartSeries {
+            series: ChartData {
+                source_range: ChartSourceRange {
+                    sources: vec![GridRange {
+                        sheet_id,
+                        start_row_index: start_row + offset,
+                        end_row_index: start_row + offset + 1,
+                        start_column_index: range.start_column,
+                        end_column_index: range.end_column,
+                    }],
+                },
+            },
+            target_axis: BasicChartAxisPosition::LeftAxis,
+            chart_type: Some(BasicChartType::Column),
+            line_style: None,
+            color: None,
+        })
+        .collect::<Vec<BasicChartSeries>>();
+
+    ChartSpec {
         title: Some(title),
         basic_chart: Some(BasicChartSpec {
             chart_type: BasicChartType::Column,
@@ -23,51 +132,16 @@ pub fn add_summary_chart(
                     source_range: ChartSourceRange {
                         sources: vec![GridRange {
                             sheet_id,
-                            start_row_index: 0,
-                            end_row_index: 1,
-                            start_column_index: 0,
-                            end_column_index: 13,
+                            start_row_index: start_row,
+                            end_row_index: start_row + 1,
+                            start_column_index: range.start_column,
+                            end_column_index: range.end_column,
                         }],
                     },
                 },
                 reversed: false,
             }],
-            series: vec![
-                BasicChartSeries {
-                    series: ChartData {
-                        source_range: ChartSourceRange {
-                            sources: vec![GridRange {
-                                sheet_id,
-                                start_row_index: 1,
-                                end_row_index: 2,
-                                start_column_index: 0,
-                                end_column_index: 13,
-                            }],
-                        },
-                    },
-                    target_axis: BasicChartAxisPosition::LeftAxis,
-                    chart_type: Some(BasicChartType::Column),
-                    line_style: None,
-                    color: None,
-                },
-                BasicChartSeries {
-                    series: ChartData {
-                        source_range: ChartSourceRange {
-                            sources: vec![GridRange {
-                                sheet_id,
-                                start_row_index: 2,
-                                end_row_index: 3,
-                                start_column_index: 0,
-                                end_column_index: 13,
-                            }],
-                        },
-                    },
-                    target_axis: BasicChartAxisPosition::LeftAxis,
-                    chart_type: Some(BasicChartType::Column),
-                    line_style: None,
-                    color: None,
-                },
-            ],
+            series,
             header_count: 1,
             three_dimensional: false,
             interpolate_nulls: false,
@@ -76,27 +150,5 @@ pub fn add_summary_chart(
             compare_mode: BasicChartCompareMode::Category,
         }),
         ..Default::default()
-    };
-
-    let chart_req = SpreadsheetBatchUpdate {
-        requests: vec![Request {
-            add_sheet: None,
-            add_chart: Some(AddChartRequest {
-                chart: EmbeddedChart {
-                    chart_id: None,
-                    spec: chart_spec,
-                    position: EmbeddedObjectPosition {
-                        new_sheet: true,
-                        ..Default::default()
-                    },
-                },
-            }),
-        }],
-        response_ranges: Vec::new(),
-        response_include_grid_data: false,
-        include_spreadsheet_in_response: false,
-    };
-
-    client.batch_update_spreadsheet(token, spreadsheet_id, &chart_req)?;
-    Ok(())
+    }
 }
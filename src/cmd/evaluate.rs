@@ -1,98 +1,333 @@
-use anyhow::bail;
+use anyhow::{anyhow, bail, ensure};
 
 use super::handle_auth;
 use crate::chart;
 use crate::config::{self, ResponseKind};
 use crate::drive;
+use crate::local;
+use crate::output;
+use crate::render::{self, ImageFormat};
 use crate::sheets;
-use crate::survey::{summary::Summary, Survey};
+use crate::sheets::spreadsheets_values::SpreadsheetValueRange;
+use crate::survey::anonymity::{self, AnonymityConfig};
+use crate::survey::dedup::{self, DedupPolicy};
+use crate::survey::identity::{self, IdentityConfig};
+use crate::survey::mapping::SheetMapping;
+use crate::survey::rater::RaterConfig;
+use crate::survey::timestamp::{self, TimeWindow};
+use crate::survey::{self, gap::GapConfig, summary::Summary, Responses, Survey};
 
 const SUMMARY_SHEET_NAME: &str = "Chart and Summary";
 const CHART_NAME: &str = "Chart Results";
 
 pub struct Evaluator {
-    _auth_client: gauth::Auth,
+    _auth_client: Option<gauth::Auth>,
 }
 
 impl Evaluator {
-    pub fn new(auth_client: gauth::Auth) -> Self {
+    pub fn new(auth_client: Option<gauth::Auth>) -> Self {
         Evaluator {
             _auth_client: auth_client,
         }
     }
 
     pub fn run(&self, args: clap::ArgMatches) -> anyhow::Result<()> {
-        let token = self._auth_client.access_token(handle_auth)?;
         let flags = Flags::default().parse(args)?;
 
-        println!("entered id: {}", flags.spreadsheet_id);
         println!("entered templates file: {}", flags.config_file);
 
-        let client = sheets::Client::new();
-        let spreadsheet = client.get_spreadsheet(&token.access_token, &flags.spreadsheet_id)?;
-        let spreadsheet_client = drive::SpreadsheetClient::new(&client, &token.access_token);
-
         let templates = config::read(
-            flags.config_file,
+            &flags.config_file,
             vec![("{name}", flags.first_name.as_ref())],
         )?;
 
-        let spreadsheet_data =
-            spreadsheet_client.retrieve_sheet_data(&spreadsheet.sheets, &flags.spreadsheet_id)?;
+        let mut mapping = flags.mapping.clone();
+        mapping.add_templates(&templates);
+
+        match flags.source {
+            Source::Spreadsheets(ref spreadsheet_ids) => {
+                self.eval_spreadsheets(spreadsheet_ids, &templates, &mapping, &flags)
+            }
+            Source::Files(ref files) => self.eval_files(files, &templates, &mapping, &flags),
+        }
+    }
 
-        let mut summary = Summary::new();
+    // The responses of all spreadsheets are merged into one evaluation,
+    // the summary is saved to the first one
+    fn eval_spreadsheets(
+        &self,
+        spreadsheet_ids: &[String],
+        templates: &[config::QuestionConfig],
+        mapping: &SheetMapping,
+        flags: &Flags,
+    ) -> anyhow::Result<()> {
+        println!("entered ids: {:?}", spreadsheet_ids);
 
-        for response_kind in [ResponseKind::Grade, ResponseKind::Text].iter() {
-            let templates_by_kind = templates
-                .iter()
-                .cloned()
-                .filter(|tmplt| {
-                    // todo: search for discriminators separately
-                    tmplt.response_kind == *response_kind
-                        || tmplt.response_kind == ResponseKind::Discriminator
-                })
-                .collect::<Vec<config::QuestionConfig>>();
+        let token = self
+            ._auth_client
+            .as_ref()
+            .ok_or_else(|| anyhow!("can't init auth client: is `OAUTH_CFG_FILE` set?"))?
+            .access_token(handle_auth)?;
 
-            println!("> scanning for: {}", response_kind);
+        let client = sheets::Client::new();
+        let spreadsheet_client = drive::SpreadsheetClient::new(&client, &token.access_token);
 
-            let survey = Survey::new(&templates_by_kind);
-            let responses = survey.scan_all(&spreadsheet_data)?;
+        let mut spreadsheets = Vec::with_capacity(spreadsheet_ids.len());
+        let mut spreadsheet_data = Vec::new();
+        let mut sheet_kinds = Vec::new();
+        for spreadsheet_id in spreadsheet_ids {
+            let spreadsheet =
+                client.get_spreadsheet(token.access_token.as_str(), spreadsheet_id)?;
 
-            summary.set_by_kind(response_kind, responses);
+            let (data, kinds) = spreadsheet_client.retrieve_sheet_data(
+                &spreadsheet.sheets,
+                spreadsheet_id,
+                mapping,
+                &[SUMMARY_SHEET_NAME],
+            )?;
+            spreadsheet_data.extend(data);
+            sheet_kinds.extend(kinds);
+            spreadsheets.push(spreadsheet);
         }
+        ensure!(
+            !spreadsheet_data.is_empty(),
+            "no response sheets found, check the `sheet` mapping"
+        );
+
+        prepare(&mut spreadsheet_data, &sheet_kinds, templates, flags);
+        let summary = summarize(templates, &spreadsheet_data, sheet_kinds, flags)?;
+        write_charts(&summary, flags)?;
 
-        let summary_sheet_id =
-            spreadsheet_client.add_summary_sheet(SUMMARY_SHEET_NAME, &flags.spreadsheet_id)?;
+        if flags.output != output::Format::Sheet {
+            return output::write(
+                flags.output,
+                &summary,
+                &flags.first_name,
+                &flags.html,
+                flags.output_file.as_deref(),
+            );
+        }
 
-        spreadsheet_client.save_summary(SUMMARY_SHEET_NAME, &flags.spreadsheet_id, summary)?;
+        let spreadsheet = &spreadsheets[0];
+        let (summary_sheet_id, summary_sheet_title) =
+            spreadsheet_client.prepare_summary_sheet(SUMMARY_SHEET_NAME, spreadsheet)?;
 
-        chart::add_summary_chart(
-            &client,
-            &token.access_token,
-            &flags.spreadsheet_id,
-            summary_sheet_id,
-            String::from(CHART_NAME),
+        let written = spreadsheet_client.save_summary(
+            &summary_sheet_title,
+            &spreadsheet.spreadsheet_id,
+            &summary,
         )?;
 
+        let charted = summary.chart_table().and_then(|(title, series_count)| {
+            written
+                .iter()
+                .find(|(written_title, _)| written_title == title)
+                .map(|(_, range)| (*range, series_count))
+        });
+
+        if let Some((range, series_count)) = charted {
+            chart::save_summary_chart(
+                &client,
+                &token.access_token,
+                spreadsheet,
+                summary_sheet_id,
+                String::from(CHART_NAME),
+                range,
+                series_count as u64,
+            )?;
+        }
+
         Ok(())
     }
+
+    // Files are mapped by their name without extension, i.e. `team-feedback` for `team-feedback.csv`
+    fn eval_files(
+        &self,
+        files: &[String],
+        templates: &[config::QuestionConfig],
+        mapping: &SheetMapping,
+        flags: &Flags,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            flags.output != output::Format::Sheet,
+            "`sheet` output requires the `sheet-id` argument"
+        );
+
+        let mut files_data = Vec::new();
+        let mut sheet_kinds = Vec::new();
+        for data in local::read_sheet_data(files)? {
+            match mapping.select(None, &data.range, None, &[SUMMARY_SHEET_NAME]) {
+                Some(kind) => {
+                    files_data.push(data);
+                    sheet_kinds.push(kind);
+                }
+                None => println!("skipping unmapped file: > {}", data.range),
+            }
+        }
+        ensure!(
+            !files_data.is_empty(),
+            "no response files left, check the `sheet` mapping"
+        );
+
+        prepare(&mut files_data, &sheet_kinds, templates, flags);
+        let summary = summarize(templates, &files_data, sheet_kinds, flags)?;
+        write_charts(&summary, flags)?;
+
+        output::write(
+            flags.output,
+            &summary,
+            &flags.first_name,
+            &flags.html,
+            flags.output_file.as_deref(),
+        )
+    }
+}
+
+// Narrows the raw responses down to a single submission per respondent within the review window
+// and strips the respondent identities
+fn prepare(
+    sheet_data: &mut [SpreadsheetValueRange],
+    sheet_kinds: &[Option<String>],
+    templates: &[config::QuestionConfig],
+    flags: &Flags,
+) {
+    survey::insert_respondent_columns(sheet_data, templates);
+    survey::number_rows(sheet_data);
+    if flags.window.is_set() {
+        timestamp::filter(sheet_data, &flags.window).print();
+    }
+    let groups = dedup::sheet_groups(sheet_data, sheet_kinds, templates);
+    dedup::dedup(sheet_data, &groups, flags.dedup, flags.window.date_order).print();
+    identity::anonymize(sheet_data, &flags.identity);
+}
+
+fn write_charts(summary: &Summary, flags: &Flags) -> anyhow::Result<()> {
+    match flags.chart_dir {
+        Some(ref dir) => {
+            render::write_charts(summary, dir, &flags.first_name, &flags.chart_formats)
+        }
+        None => Ok(()),
+    }
+}
+
+fn summarize(
+    templates: &[config::QuestionConfig],
+    sheet_data: &[SpreadsheetValueRange],
+    sheet_kinds: Vec<Option<String>>,
+    flags: &Flags,
+) -> anyhow::Result<Summary> {
+    let mut summary = Summary::new();
+    summary.set_show_unweighted(flags.show_unweighted);
+    summary.set_gap_config(flags.gap_config.clone());
+    summary.set_rater_config(flags.raters.clone());
+
+    println!("> scanning responses");
+
+    let mut survey = Survey::new(templates);
+    survey.set_slice_by(flags.slice_by.clone());
+    survey.set_sheet_kinds(sheet_kinds);
+    let (responses, report) = survey.scan_all(sheet_data)?;
+
+    report.print();
+    ensure!(
+        !flags.strict || report.is_empty(),
+        "validation failed with {} issue(s)",
+        report.issues.len()
+    );
+
+    let (responses, suppressions) = anonymity::apply(responses, &flags.anonymity);
+    for suppression in &suppressions {
+        eprintln!("> anonymity: {}", suppression);
+    }
+
+    let mut responses = responses;
+    for response_kind in [
+        ResponseKind::Grade,
+        ResponseKind::Text,
+        ResponseKind::Choice,
+        ResponseKind::Checkbox,
+        ResponseKind::Numeric,
+        ResponseKind::Nps,
+    ]
+    .iter()
+    {
+        let (of_kind, rest): (Vec<Responses>, Vec<Responses>) = responses
+            .into_iter()
+            .partition(|r| r.response_kind == *response_kind);
+
+        summary.set_by_kind(response_kind, of_kind);
+        responses = rest;
+    }
+    summary.set_validation(report);
+    summary.set_suppressions(suppressions);
+
+    Ok(summary)
+}
+
+enum Source {
+    Spreadsheets(Vec<String>),
+    Files(Vec<String>),
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Source::Files(Vec::new())
+    }
 }
 
-#[derive(Default)]
 struct Flags {
-    spreadsheet_id: String,
+    source: Source,
     config_file: String,
     first_name: String,
+    output: output::Format,
+    output_file: Option<String>,
+    html: output::HtmlConfig,
+    show_unweighted: bool,
+    gap_config: GapConfig,
+    raters: RaterConfig,
+    strict: bool,
+    slice_by: Vec<String>,
+    anonymity: AnonymityConfig,
+    identity: IdentityConfig,
+    dedup: DedupPolicy,
+    window: TimeWindow,
+    mapping: SheetMapping,
+    chart_dir: Option<String>,
+    chart_formats: Vec<ImageFormat>,
+}
+
+impl Default for Flags {
+    fn default() -> Self {
+        Flags {
+            source: Source::default(),
+            config_file: String::new(),
+            first_name: String::new(),
+            output: output::Format::Sheet,
+            output_file: None,
+            html: output::HtmlConfig::default(),
+            show_unweighted: false,
+            gap_config: GapConfig::default(),
+            raters: RaterConfig::default(),
+            strict: false,
+            slice_by: Vec::new(),
+            anonymity: AnonymityConfig::default(),
+            identity: IdentityConfig::default(),
+            dedup: DedupPolicy::Latest,
+            window: TimeWindow::default(),
+            mapping: SheetMapping::default(),
+            chart_dir: None,
+            chart_formats: vec![ImageFormat::Svg, ImageFormat::Png],
+        }
+    }
 }
 
 impl Flags {
     fn parse(mut self, args: clap::ArgMatches) -> anyhow::Result<Self> {
-        let keys = ["sheet-id", "template", "first-name"];
+        let keys = ["template", "first-name"];
         for key in keys.iter() {
             if let Some(v) = args.value_of(key) {
                 let v = v.to_owned();
                 match *key {
-                    "sheet-id" => self.spreadsheet_id = v,
                     "template" => self.config_file = v,
                     "first-name" => self.first_name = v,
                     _ => {}
@@ -101,6 +336,167 @@ impl Flags {
                 bail!("Argument `{}` not found", key);
             }
         }
+
+        self.source = match (args.values_of("sheet-id"), args.values_of("input")) {
+            (Some(spreadsheet_ids), _) => {
+                Source::Spreadsheets(spreadsheet_ids.map(String::from).collect())
+            }
+            (None, Some(files)) => Source::Files(files.map(String::from).collect()),
+            (None, None) => bail!("Either `sheet-id` or `input` argument is required"),
+        };
+
+        self.output = match (args.value_of("output"), &self.source) {
+            (Some(format), _) => format.parse()?,
+            (None, Source::Spreadsheets(_)) => output::Format::Sheet,
+            (None, Source::Files(_)) => output::Format::Markdown,
+        };
+        self.output_file = args.value_of("output-file").map(String::from);
+
+        // a report per person, i.e. `john.html`
+        if self.output == output::Format::Html {
+            if self.output_file.is_none() {
+                self.output_file = Some(format!("{}.html", self.first_name.to_lowercase()));
+            }
+            if let Some(v) = args.value_of("report-template") {
+                self.html.set_template_file(v)?;
+            }
+        }
+        self.show_unweighted = args.is_present("show-unweighted");
+        self.strict = args.is_present("strict");
+        self.slice_by = args
+            .values_of("slice-by")
+            .map(|categories| categories.map(String::from).collect())
+            .unwrap_or_default();
+
+        if let Some(v) = args.value_of("self-kind") {
+            self.gap_config.self_kind = v.to_owned();
+            self.raters.self_kind = v.to_owned();
+        }
+        if let Some(v) = args.value_of("team-kind") {
+            self.gap_config.team_kind = v.to_owned();
+        }
+        if let Some(v) = args.value_of("gap-threshold") {
+            self.gap_config.gap_threshold = v
+                .parse()
+                .map_err(|err| anyhow!("invalid `gap-threshold`: {}", err))?;
+        }
+        if let Some(v) = args.value_of("strength-threshold") {
+            self.gap_config.strength_threshold = v
+                .parse()
+                .map_err(|err| anyhow!("invalid `strength-threshold`: {}", err))?;
+        }
+
+        if let Some(values) = args.values_of("rater-group") {
+            for v in values {
+                self.raters.add(v)?;
+            }
+        }
+
+        if let Some(v) = args.value_of("min-respondents") {
+            self.anonymity.min_respondents = v
+                .parse()
+                .map_err(|err| anyhow!("invalid `min-respondents`: {}", err))?;
+        }
+        if let Some(values) = args.values_of("group-min-respondents") {
+            for v in values {
+                let mut parts = v.rsplitn(2, '=');
+                let (threshold, group) = match (parts.next(), parts.next()) {
+                    (Some(threshold), Some(group)) => (threshold, group),
+                    _ => bail!("invalid `group-min-respondents`: {}, expected GROUP=K", v),
+                };
+
+                self.anonymity.per_group.insert(
+                    group.trim().to_lowercase(),
+                    threshold
+                        .trim()
+                        .parse()
+                        .map_err(|err| anyhow!("invalid `group-min-respondents`: {}", err))?,
+                );
+            }
+        }
+
+        // the manager and self-assessment are exempt from the anonymity threshold
+        self.anonymity.raters = self.raters.clone();
+
+        if let Some(v) = args.value_of("respondent-ids") {
+            self.identity.mode = v.parse()?;
+        }
+        self.identity.salt = args.value_of("salt").map(String::from);
+
+        if let Some(v) = args.value_of("dedup") {
+            self.dedup = v.parse()?;
+        }
+
+        if let Some(v) = args.value_of("since") {
+            self.window.set_since(v)?;
+        }
+        if let Some(v) = args.value_of("until") {
+            self.window.set_until(v)?;
+        }
+        if let Some(v) = args.value_of("date-order") {
+            self.window.date_order = v.parse()?;
+        }
+
+        if let Some(values) = args.values_of("sheet") {
+            for v in values {
+                self.mapping.add(v)?;
+            }
+        }
+
+        self.chart_dir = args.value_of("chart-dir").map(String::from);
+        if let Some(values) = args.values_of("chart-format") {
+            self.chart_formats = values
+                .map(str::parse)
+                .collect::<anyhow::Result<Vec<ImageFormat>>>()?;
+        }
+
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn evaluates_the_sample_export() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let flags = Flags {
+            first_name: String::from("John"),
+            strict: true,
+            ..Flags::default()
+        };
+
+        let templates =
+            config::read(root.join("overall-grading.csv"), vec![("{name}", "John")]).unwrap();
+        let mut sheet_data = local::read_sheet_data(&[root.join("sample_input.csv")]).unwrap();
+        let sheet_kinds = vec![None; sheet_data.len()];
+
+        prepare(&mut sheet_data, &sheet_kinds, &templates, &flags);
+        let summary = summarize(&templates, &sheet_data, sheet_kinds, &flags).unwrap();
+
+        let mut categories = templates
+            .iter()
+            .filter(|template| {
+                template.response_kind == ResponseKind::Grade
+                    && template.assessment_kind == "Self-Assessment"
+            })
+            .map(|template| template.category.as_str())
+            .collect::<Vec<&str>>();
+        categories.dedup();
+
+        let grades = summary.document().grade;
+        assert_eq!(
+            grades
+                .iter()
+                .map(|entry| entry.category.as_str())
+                .collect::<Vec<&str>>(),
+            categories
+        );
+        assert!(grades
+            .iter()
+            .all(|entry| entry.assessment_kind == "Self-Assessment" && entry.result.is_some()));
+    }
+}
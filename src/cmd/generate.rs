@@ -1,9 +1,37 @@
 use std::default::Default;
+use std::str::FromStr;
 
-use anyhow::{anyhow, bail};
+use anyhow::{anyhow, bail, ensure};
 
-use crate::appsscript::{template::Template, ProjectsClient};
-use crate::config::{self, QuestionConfig, ResponseKind};
+use crate::appsscript::template::{self, Template};
+use crate::appsscript::{DeploymentConfig, ExecutionRequest, Manifest, ProjectsClient};
+use crate::config::{self, FormConfig, QuestionConfig, ResponseKind};
+use crate::forms::{self, FormLinks};
+
+use forms::form::*;
+use forms::forms_batch_update::*;
+
+// Entry point of the generated script
+const CREATE_FORM_FUNCTION: &str = "createForm";
+
+// Sections of the generated form, as laid out by the `createForm` script
+const GRADES_SECTION_TITLE: &str = "Agree or Disagree";
+const GRADES_SECTION_HELP: &str = "Agree or disagree with the provided statements. The scale should be interpreted as follows: 1 - strongly disagree, 3 - neutral, 5 - strongly agree.";
+const GRADES_SECTION_MIXED_HELP: &str = "Agree or disagree with the provided statements. The meaning of the lowest and the highest grade is shown with every statement.";
+const OTHERS_SECTION_TITLE: &str = "Further Questions";
+const OTHERS_SECTION_HELP: &str = "Pick the options or enter the numbers that apply.";
+const TEXTS_SECTION_TITLE: &str = "Strengths and Improvements";
+const TEXTS_SECTION_HELP: &str = "You have 2 boxes to add a text or list about your Strengths and Improvements. The text in this section will be shared directly.";
+
+// Kinds of the questions asked in the generated form
+const FORM_KINDS: [ResponseKind; 6] = [
+    ResponseKind::Grade,
+    ResponseKind::Choice,
+    ResponseKind::Checkbox,
+    ResponseKind::Numeric,
+    ResponseKind::Nps,
+    ResponseKind::Text,
+];
 
 pub struct Generator {
     _auth_client: gauth::Auth,
@@ -20,21 +48,42 @@ impl Generator {
         let flags = Flags::default().parse(args)?;
         let token = self._auth_client.access_token(super::handle_auth)?;
 
+        let templates = config::read(&flags.template_file, vec![("{name}", &flags.first_name)])?;
+
+        let questions = self.config_questions(&templates, &FORM_KINDS, &flags.assessment_kind);
+        for question in questions
+            .iter()
+            .filter(|question| question.response_kind == ResponseKind::Grade)
+        {
+            check_form_scale(question)?;
+        }
+
+        let sections = match flags.layout {
+            Layout::Kind => kind_sections(&questions),
+            Layout::Category => category_sections(&questions)?,
+        };
+
+        let links = match flags.backend {
+            Backend::AppsScript => self.generate_script(&token.access_token, flags, &sections)?,
+            Backend::Forms => self.generate_form(&token.access_token, &flags, &sections)?,
+        };
+
+        links.print();
+        Ok(())
+    }
+
+    // Uploads the `createForm` script to a new Apps Script project and runs it through the Apps Script API
+    fn generate_script(
+        &self,
+        access_token: &str,
+        flags: Flags,
+        sections: &[Section],
+    ) -> anyhow::Result<FormLinks> {
         let title = format!(
             "{}-{}-{}-{}",
             &flags.assessment_kind, &flags.first_name, &flags.last_name, &flags.occasion
         );
 
-        let templates = config::read(&flags.template_file, vec![("{name}", &flags.first_name)])?;
-
-        let graded_questions =
-            self.config_questions(&templates, ResponseKind::Grade, &flags.assessment_kind)?;
-        let text_questions =
-            self.config_questions(&templates, ResponseKind::Text, &flags.assessment_kind)?;
-
-        // println!("{:#?}", graded_questions);
-        // println!("{:#?}", text_questions);
-
         let code_template = Template::new(
             flags.assessment_kind.as_ref(),
             flags.first_name,
@@ -42,39 +91,502 @@ impl Generator {
             flags.occasion,
             flags.drive_dir_id,
             flags.description,
-            graded_questions,
-            text_questions,
+            &flags.settings,
+            shuffle_questions(sections, flags.settings.shuffle.unwrap_or(true))?,
+            sections
+                .iter()
+                .map(|section| {
+                    template::Section::new(&section.title, &section.help, &section.questions)
+                })
+                .collect(),
         );
 
         let projects_client = ProjectsClient::new();
-        let project = projects_client.create_project(&token.access_token, title)?;
+        let project = projects_client.create_project(access_token, title.clone())?;
         let script_id = project
             .script_id
             .ok_or_else(|| anyhow!("could not retrieve script_id"))?;
 
-        projects_client.update_content(
-            &token.access_token,
+        let content = projects_client.update_content(
+            access_token,
             script_id.as_ref(),
-            code_template.code(),
+            code_template.code()?,
+            &Manifest::new(
+                flags.settings.time_zone.clone(),
+                flags.settings.exception_logging,
+            ),
         )?;
+        ensure!(
+            content.defines(CREATE_FORM_FUNCTION),
+            "the uploaded script has no `{}` function",
+            CREATE_FORM_FUNCTION
+        );
 
-        Ok(())
+        // only a versioned deployment can be executed by the API
+        let version = projects_client.create_version(access_token, &script_id, title.clone())?;
+        let version_number = version
+            .version_number
+            .ok_or_else(|| anyhow!("could not retrieve the version number"))?;
+
+        let deployment = projects_client.create_deployment(
+            access_token,
+            &DeploymentConfig {
+                script_id: script_id.clone(),
+                version_number,
+                manifest_file_name: "appsscript".to_owned(),
+                description: title,
+            },
+        )?;
+
+        println!(
+            "> running {} of script: {}",
+            CREATE_FORM_FUNCTION, script_id
+        );
+        let result = projects_client
+            .run(
+                access_token,
+                &deployment.deployment_id,
+                &ExecutionRequest {
+                    function: CREATE_FORM_FUNCTION.to_owned(),
+                    ..Default::default()
+                },
+            )
+            .map_err(|e| {
+                anyhow!(
+                    "{}\n> the script was uploaded, run `{}` from the editor: https://script.google.com/d/{}/edit",
+                    e,
+                    CREATE_FORM_FUNCTION,
+                    script_id
+                )
+            })?;
+
+        Ok(serde_json::from_value::<FormLinks>(result)?)
     }
 
-    fn config_questions(
+    // Creates the form with the Forms API, laid out like the one of the `createForm` script
+    fn generate_form(
         &self,
-        templates: &[QuestionConfig],
-        response_kind: ResponseKind,
+        access_token: &str,
+        flags: &Flags,
+        sections: &[Section],
+    ) -> anyhow::Result<FormLinks> {
+        // the Forms API has no settings for a domain restriction, shuffling or a confirmation message
+        ensure!(
+            flags.settings.allowed_domain.is_none(),
+            "the forms backend can't restrict the respondents to a domain, use the apps-script backend"
+        );
+        ensure!(
+            !shuffle_questions(sections, flags.settings.shuffle.unwrap_or(false))?,
+            "the forms backend can't shuffle the questions, use the apps-script backend or turn `shuffle` off"
+        );
+        if flags.settings.confirmation_message.is_some() {
+            println!("warning: the forms backend can't set the confirmation message, set it in the form editor");
+        }
+
+        let title = format!(
+            "{}: {} {} - {}",
+            &flags.assessment_kind, &flags.first_name, &flags.last_name, &flags.occasion
+        );
+
+        let forms_client = forms::Client::new();
+        let form = forms_client.create_form(
+            access_token,
+            &Form {
+                info: Info {
+                    title: title.clone(),
+                    document_title: Some(title),
+                    description: None,
+                },
+                ..Default::default()
+            },
+        )?;
+        let links = FormLinks::new(&form)?;
+
+        let mut requests = vec![
+            Request {
+                update_form_info: Some(UpdateFormInfoRequest {
+                    info: Info {
+                        description: Some(flags.description.clone()),
+                        ..Default::default()
+                    },
+                    update_mask: "description".to_owned(),
+                }),
+                ..Default::default()
+            },
+            // the respondents are told apart by their email, unless the form is anonymous
+            Request {
+                update_settings: Some(UpdateSettingsRequest {
+                    settings: FormSettings {
+                        email_collection_type: Some(if flags.settings.anonymous {
+                            EmailCollectionType::DoNotCollect
+                        } else {
+                            EmailCollectionType::Verified
+                        }),
+                    },
+                    update_mask: "emailCollectionType".to_owned(),
+                }),
+                ..Default::default()
+            },
+        ];
+
+        let mut items = Vec::new();
+        for section in sections {
+            items.push(page_break(&section.title, &section.help));
+            items.extend(section.questions.iter().map(|question| form_item(question)));
+        }
+
+        requests.extend(items.into_iter().enumerate().map(|(index, item)| Request {
+            create_item: Some(CreateItemRequest {
+                item,
+                location: Location { index },
+            }),
+            ..Default::default()
+        }));
+
+        forms_client.batch_update_form(
+            access_token,
+            &links.form_id,
+            &BatchUpdateFormRequest {
+                include_form_in_response: false,
+                requests,
+            },
+        )?;
+
+        forms_client.move_to_folder(access_token, &links.form_id, &flags.drive_dir_id)?;
+
+        Ok(links)
+    }
+
+    fn config_questions<'a>(
+        &self,
+        templates: &'a [QuestionConfig],
+        response_kinds: &[ResponseKind],
         assessment_kind: &str,
-    ) -> anyhow::Result<Vec<String>> {
-        Ok(templates
+    ) -> Vec<&'a QuestionConfig> {
+        templates
             .iter()
             .filter(|question| {
-                (question.response_kind == response_kind)
+                response_kinds.contains(&question.response_kind)
                     && (question.assessment_kind.to_lowercase() == *assessment_kind.to_lowercase())
             })
-            .map(|question| question.template_final.to_string())
-            .collect::<Vec<String>>())
+            .collect::<Vec<&QuestionConfig>>()
+    }
+}
+
+// A page of the generated form, the questions in the order of the template
+struct Section<'a> {
+    title: String,
+    help: String,
+    questions: Vec<&'a QuestionConfig>,
+    // the `Shuffle` of the category, if set
+    shuffle: Option<bool>,
+}
+
+// The default layout: a section of graded questions, one of the `choice`, `checkbox`,
+// `numeric` and `nps` questions and one of text questions
+fn kind_sections<'a>(questions: &[&'a QuestionConfig]) -> Vec<Section<'a>> {
+    let of_kinds = |kinds: &[ResponseKind]| {
+        questions
+            .iter()
+            .filter(|question| kinds.contains(&question.response_kind))
+            .copied()
+            .collect::<Vec<&QuestionConfig>>()
+    };
+
+    let graded = of_kinds(&[ResponseKind::Grade]);
+    let sections = vec![
+        Section {
+            title: GRADES_SECTION_TITLE.to_owned(),
+            help: grades_help(&graded),
+            questions: graded,
+            shuffle: None,
+        },
+        Section {
+            title: OTHERS_SECTION_TITLE.to_owned(),
+            help: OTHERS_SECTION_HELP.to_owned(),
+            questions: of_kinds(&[
+                ResponseKind::Choice,
+                ResponseKind::Checkbox,
+                ResponseKind::Numeric,
+                ResponseKind::Nps,
+            ]),
+            shuffle: None,
+        },
+        Section {
+            title: TEXTS_SECTION_TITLE.to_owned(),
+            help: TEXTS_SECTION_HELP.to_owned(),
+            questions: of_kinds(&[ResponseKind::Text]),
+            shuffle: None,
+        },
+    ];
+
+    sections
+        .into_iter()
+        .filter(|section| !section.questions.is_empty())
+        .collect()
+}
+
+// A section per category in the order of the template, titled by the category
+fn category_sections<'a>(questions: &[&'a QuestionConfig]) -> anyhow::Result<Vec<Section<'a>>> {
+    let mut categories: Vec<&str> = Vec::new();
+    for question in questions {
+        if !categories.contains(&question.category.as_str()) {
+            categories.push(&question.category);
+        }
+    }
+
+    let mut sections = Vec::new();
+    for category in categories {
+        let questions = questions
+            .iter()
+            .filter(|question| question.category == category)
+            .copied()
+            .collect::<Vec<&QuestionConfig>>();
+
+        let description =
+            category_setting(category, &questions, "CategoryDescription", |question| {
+                question.category_description()
+            })?;
+        let shuffle =
+            category_setting(category, &questions, "Shuffle", |question| question.shuffle)?;
+
+        // without a description the graded questions are explained like in the default layout
+        let graded = questions
+            .iter()
+            .filter(|question| question.response_kind == ResponseKind::Grade)
+            .copied()
+            .collect::<Vec<&QuestionConfig>>();
+        let help = match description {
+            Some(description) => description.to_owned(),
+            None if !graded.is_empty() => grades_help(&graded),
+            None => String::new(),
+        };
+
+        sections.push(Section {
+            title: category.to_owned(),
+            help,
+            questions,
+            shuffle,
+        });
+    }
+
+    Ok(sections)
+}
+
+// A category setting may be given on any of its questions, but not differently
+fn category_setting<'a, T: PartialEq + std::fmt::Debug>(
+    category: &str,
+    questions: &[&'a QuestionConfig],
+    column: &str,
+    setting: impl Fn(&'a QuestionConfig) -> Option<T>,
+) -> anyhow::Result<Option<T>> {
+    let mut value = None;
+    for question in questions {
+        match (&value, setting(question)) {
+            (None, Some(v)) => value = Some(v),
+            (Some(set), Some(v)) => ensure!(
+                *set == v,
+                "conflicting {} of category `{}`: {:?} and {:?}",
+                column,
+                category,
+                set,
+                v
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(value)
+}
+
+// Forms shuffle the questions of every page for every respondent, or of none of the pages.
+// The `Shuffle` of the categories falls back to the form settings and has to be the same on all pages.
+fn shuffle_questions(sections: &[Section], default: bool) -> anyhow::Result<bool> {
+    let mut shuffled: Option<(bool, &str)> = None;
+    for section in sections {
+        let shuffle = section.shuffle.unwrap_or(default);
+        match shuffled {
+            None => shuffled = Some((shuffle, &section.title)),
+            Some((set, title)) => ensure!(
+                set == shuffle,
+                "the form shuffles the questions of all pages or of none, `{}` is {} and `{}` is {}",
+                title,
+                if set { "shuffled" } else { "not shuffled" },
+                section.title,
+                if shuffle { "shuffled" } else { "not shuffled" }
+            ),
+        }
+    }
+
+    Ok(shuffled.map_or(default, |(shuffle, _)| shuffle))
+}
+
+// Both backends take whole numbers from 0 or 1 up to 3..10 as scale bounds
+fn check_form_scale(question: &QuestionConfig) -> anyhow::Result<()> {
+    let (low, high) = question.scale();
+    ensure!(
+        low.fract() == 0.0
+            && high.fract() == 0.0
+            && (low == 0.0 || low == 1.0)
+            && (3.0..=10.0).contains(&high),
+        "unsupported form scale of `{}`: {} to {}, the scale goes from 0 or 1 up to 3..10",
+        question.title(),
+        low,
+        high
+    );
+
+    Ok(())
+}
+
+// The grades section explains the scale, if all questions share one
+fn grades_help(graded_questions: &[&QuestionConfig]) -> String {
+    if graded_questions
+        .iter()
+        .all(|question| question.has_default_scale())
+    {
+        return GRADES_SECTION_HELP.to_owned();
+    }
+
+    let first = match graded_questions.first() {
+        Some(first) => first,
+        None => return GRADES_SECTION_HELP.to_owned(),
+    };
+
+    let shared = graded_questions
+        .iter()
+        .all(|question| question.scale() == first.scale() && question.labels() == first.labels());
+    if !shared {
+        return GRADES_SECTION_MIXED_HELP.to_owned();
+    }
+
+    let (low, high) = first.scale();
+    let (low_label, high_label) = first.labels();
+    format!(
+        "Agree or disagree with the provided statements. The scale should be interpreted as follows: {} - {}, {} - {}.",
+        low, low_label, high, high_label
+    )
+}
+
+fn page_break(title: &str, description: &str) -> Item {
+    Item {
+        title: title.to_owned(),
+        description: Some(description.to_owned()),
+        page_break_item: Some(PageBreakItem {}),
+        ..Default::default()
+    }
+}
+
+fn form_item(question: &QuestionConfig) -> Item {
+    match question.response_kind {
+        ResponseKind::Choice | ResponseKind::Checkbox => question_item(
+            question,
+            Question {
+                required: true,
+                choice_question: Some(ChoiceQuestion {
+                    choice_type: if question.response_kind == ResponseKind::Choice {
+                        ChoiceType::Radio
+                    } else {
+                        ChoiceType::Checkbox
+                    },
+                    options: question
+                        .options()
+                        .into_iter()
+                        .map(|option| ChoiceOption {
+                            value: option.to_owned(),
+                        })
+                        .collect(),
+                }),
+                ..Default::default()
+            },
+        ),
+        // the Forms API has no number validation, the answers are checked by `eval`
+        ResponseKind::Numeric => question_item(
+            question,
+            Question {
+                required: true,
+                text_question: Some(TextQuestion { paragraph: false }),
+                ..Default::default()
+            },
+        ),
+        ResponseKind::Text => question_item(
+            question,
+            Question {
+                required: true,
+                text_question: Some(TextQuestion { paragraph: true }),
+                ..Default::default()
+            },
+        ),
+        _ => scale_item(question),
+    }
+}
+
+fn scale_item(config: &QuestionConfig) -> Item {
+    let (low, high) = config.scale();
+    let (low_label, high_label) = config.labels();
+    question_item(
+        config,
+        Question {
+            required: true,
+            scale_question: Some(ScaleQuestion {
+                low: low as i32,
+                high: high as i32,
+                low_label: Some(low_label.to_owned()),
+                high_label: Some(high_label.to_owned()),
+            }),
+            ..Default::default()
+        },
+    )
+}
+
+fn question_item(config: &QuestionConfig, question: Question) -> Item {
+    Item {
+        title: config.title(),
+        question_item: Some(QuestionItem { question }),
+        ..Default::default()
+    }
+}
+
+// Form backend of `generate`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Backend {
+    // An Apps Script project with the `createForm` function, run through the Apps Script API
+    #[default]
+    AppsScript,
+    // The form itself, created through the Forms API
+    Forms,
+}
+
+impl FromStr for Backend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "apps-script" => Ok(Backend::AppsScript),
+            "forms" => Ok(Backend::Forms),
+            _ => Err(anyhow!("unknown form backend: {}", s)),
+        }
+    }
+}
+
+// How the questions are split into the pages of the form
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Layout {
+    // Graded, further and text questions
+    #[default]
+    Kind,
+    // A page per category
+    Category,
+}
+
+impl FromStr for Layout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "kind" => Ok(Layout::Kind),
+            "category" => Ok(Layout::Category),
+            _ => Err(anyhow!("unknown form layout: {}", s)),
+        }
     }
 }
 
@@ -87,6 +599,9 @@ struct Flags {
     drive_dir_id: String,
     template_file: String,
     description: String,
+    backend: Backend,
+    layout: Layout,
+    settings: FormConfig,
 }
 
 impl Flags {
@@ -120,6 +635,18 @@ impl Flags {
             }
         }
 
+        if let Some(v) = args.value_of("backend") {
+            self.backend = v.parse()?;
+        }
+
+        if let Some(v) = args.value_of("layout") {
+            self.layout = v.parse()?;
+        }
+
+        if let Some(v) = args.value_of("form-settings") {
+            self.settings = FormConfig::read(v)?;
+        }
+
         Ok(self)
     }
 }
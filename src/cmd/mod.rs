@@ -25,11 +25,12 @@ impl FromStr for Cmd {
     type Err = io_err;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let auth_client = auth_client().expect("can't init auth client");
-
         match s.to_lowercase().as_str() {
-            "generate" => Ok(Cmd::Generate(generate::Generator::new(auth_client))),
-            "eval" => Ok(Cmd::Evaluate(evaluate::Evaluator::new(auth_client))),
+            "generate" => Ok(Cmd::Generate(generate::Generator::new(
+                auth_client().expect("can't init auth client"),
+            ))),
+            // the auth client is optional for `eval`: local csv input works without it
+            "eval" => Ok(Cmd::Evaluate(evaluate::Evaluator::new(auth_client().ok()))),
             _ => Err(io_err::new(
                 io_err_kind::InvalidInput,
                 format!("unknown command: {}", s),
@@ -49,6 +50,11 @@ fn auth_client() -> Result<gauth::Auth, Box<dyn Error>> {
             "https://www.googleapis.com/auth/spreadsheets",
             "https://www.googleapis.com/auth/spreadsheets.readonly",
             "https://www.googleapis.com/auth/script.projects",
+            "https://www.googleapis.com/auth/script.deployments",
+            "https://www.googleapis.com/auth/forms",
+            "https://www.googleapis.com/auth/forms.body",
+            // read by the generated script to check `allowedDomain` against the form owner
+            "https://www.googleapis.com/auth/userinfo.email",
         ],
         PathBuf::from(crd_path),
     );
@@ -1,13 +1,17 @@
 use std::{
     error::Error as std_err,
     fmt::{self, Display, Formatter},
-    fs::File,
+    fs::{self, File},
     path::Path,
 };
 
-use serde_derive::Deserialize;
+use anyhow::{anyhow, ensure};
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Eq, PartialEq, Clone, Debug, Deserialize)]
+use crate::appsscript::ExceptionLogging;
+use crate::survey::Responses;
+
+#[derive(Eq, PartialEq, Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ResponseKind {
     Grade,
@@ -16,39 +20,107 @@ pub enum ResponseKind {
     // If set in config, the responses will be assigned based on the
     // value provided in this field
     Discriminator,
+
+    // A single option of the question's `Options`
+    Choice,
+    // Any number of the question's `Options`
+    Checkbox,
+    // A free number
+    Numeric,
+    // Likelihood to recommend, 0 to 10
+    Nps,
 }
 
 impl ResponseKind {
-    pub fn process_data(&self, responses: &[String]) -> Option<String> {
+    pub fn process_data(&self, responses: &Responses) -> Option<String> {
         match self {
             ResponseKind::Grade => self.process_grades(responses),
             ResponseKind::Text => self.process_reviews(responses),
             ResponseKind::Discriminator => None,
+            ResponseKind::Choice | ResponseKind::Checkbox => self.process_choices(responses),
+            ResponseKind::Numeric => self.process_grades_unweighted(responses),
+            ResponseKind::Nps => self.process_nps(responses),
         }
     }
 
-    fn process_grades(&self, grades: &[String]) -> Option<String> {
-        if grades.is_empty() {
+    fn process_grades(&self, grades: &Responses) -> Option<String> {
+        grades.weighted_mean().map(|calc| format!("{:.1}", calc))
+    }
+
+    pub fn process_grades_unweighted(&self, grades: &Responses) -> Option<String> {
+        grades.mean().map(|calc| format!("{:.1}", calc))
+    }
+
+    // Respondents per option, most picked first, i.e. `Weekly: 3 (60%)`.
+    // The selected options of a checkbox answer are separated by new lines.
+    fn process_choices(&self, choices: &Responses) -> Option<String> {
+        let answers = choices.read();
+        if answers.is_empty() {
             return None;
         }
-        let calc = grades
+
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for option in answers.iter().flat_map(|answer| answer.value.lines()) {
+            match counts.iter_mut().find(|(counted, _)| *counted == option) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((option, 1)),
+            }
+        }
+        // the sort is stable: ties keep the order of the first answers
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        Some(
+            counts
+                .iter()
+                .map(|(option, count)| {
+                    format!(
+                        "{}: {} ({:.0}%)",
+                        option,
+                        count,
+                        *count as f32 / answers.len() as f32 * 100.0
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    }
+
+    // Share of promoters (9-10) minus the share of detractors (0-6), from -100 to 100
+    fn process_nps(&self, ratings: &Responses) -> Option<String> {
+        let ratings = ratings
+            .read()
             .iter()
-            .map(|item| {
-                item.parse::<f32>()
-                    .unwrap_or_else(|_| panic!("failed to parse: {}", item))
-                // todo: remove unwrap
-            })
-            .sum::<f32>()
-            / grades.len() as f32;
+            .filter_map(|answer| answer.value.parse::<f32>().ok())
+            .collect::<Vec<f32>>();
+        if ratings.is_empty() {
+            return None;
+        }
+
+        let share = |count: usize| count as f32 / ratings.len() as f32 * 100.0;
+        let promoters = share(ratings.iter().filter(|r| **r >= 9.0).count());
+        let detractors = share(ratings.iter().filter(|r| **r <= 6.0).count());
 
-        Some(format!("{:.1}", calc))
+        Some(format!(
+            "{:+.0} ({:.0}% promoters, {:.0}% passives, {:.0}% detractors)",
+            promoters - detractors,
+            promoters,
+            100.0 - promoters - detractors,
+            detractors
+        ))
     }
 
-    fn process_reviews(&self, reviews: &[String]) -> Option<String> {
-        if reviews.is_empty() {
+    fn process_reviews(&self, reviews: &Responses) -> Option<String> {
+        if reviews.read().is_empty() {
             None
         } else {
-            Some(reviews.join("\n"))
+            Some(
+                reviews
+                    .read()
+                    .iter()
+                    .map(|review| review.value.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("\n"),
+            )
         }
     }
 }
@@ -59,42 +131,191 @@ impl Display for ResponseKind {
             ResponseKind::Grade => write!(f, "grade"),
             ResponseKind::Text => write!(f, "text"),
             ResponseKind::Discriminator => write!(f, "discriminator"),
+            ResponseKind::Choice => write!(f, "choice"),
+            ResponseKind::Checkbox => write!(f, "checkbox"),
+            ResponseKind::Numeric => write!(f, "numeric"),
+            ResponseKind::Nps => write!(f, "nps"),
+        }
+    }
+}
+
+// Grade bounds and labels of the generated forms, unless set per question
+pub const DEFAULT_SCALE_MIN: f32 = 1.0;
+pub const DEFAULT_SCALE_MAX: f32 = 5.0;
+pub const DEFAULT_LOW_LABEL: &str = "disagree";
+pub const DEFAULT_HIGH_LABEL: &str = "agree";
+
+// Grades of all scales are evaluated as points of 0 to 100
+pub const SCORE_MAX: f32 = 100.0;
+
+// Bounds and labels of the `nps` questions, the labels can be changed per question
+pub const NPS_SCALE: (f32, f32) = (0.0, 10.0);
+pub const NPS_LOW_LABEL: &str = "not at all likely";
+pub const NPS_HIGH_LABEL: &str = "extremely likely";
+
+// Separator of the `Options` column, i.e. `Daily|Weekly|Monthly`
+pub const OPTION_SEPARATOR: char = '|';
+
+// Position of the grade on the 0 to 100 score scale
+pub fn normalize(grade: f32, (scale_min, scale_max): (f32, f32)) -> f32 {
+    (grade - scale_min) / (scale_max - scale_min) * SCORE_MAX
+}
+
+pub const DEFAULT_TIME_ZONE: &str = "Europe/Berlin";
+
+// Settings of the generated form and of its script project, the unset ones keep their defaults
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct FormConfig {
+    // Collects no emails and takes any number of responses per person.
+    // The responses can't be told apart by `eval` then.
+    pub anonymous: bool,
+
+    // Shuffles the questions within every page for every respondent, the `Shuffle` column overrides it.
+    // Unset the apps-script backend shuffles, the forms backend can't.
+    pub shuffle: Option<bool>,
+
+    // Shown after the form is submitted
+    pub confirmation_message: Option<String>,
+
+    // Only the users of the domain can respond, it has to be the domain of the form owner
+    pub allowed_domain: Option<String>,
+
+    // Manifest of the script project
+    pub time_zone: String,
+    pub exception_logging: ExceptionLogging,
+}
+
+impl Default for FormConfig {
+    fn default() -> Self {
+        FormConfig {
+            anonymous: false,
+            shuffle: None,
+            confirmation_message: None,
+            allowed_domain: None,
+            time_zone: DEFAULT_TIME_ZONE.to_owned(),
+            exception_logging: ExceptionLogging::default(),
         }
     }
 }
 
+impl FormConfig {
+    pub fn read<P: AsRef<Path>>(filename: P) -> anyhow::Result<Self> {
+        let filename = filename.as_ref();
+        let mut config: FormConfig = serde_json::from_str(&fs::read_to_string(filename)?)
+            .map_err(|e| anyhow!("invalid form settings {}: {}", filename.display(), e))?;
+
+        config.confirmation_message = non_empty(&config.confirmation_message).map(String::from);
+        config.allowed_domain = non_empty(&config.allowed_domain)
+            .map(|domain| domain.trim_start_matches('@').to_lowercase());
+        ensure!(
+            !config.time_zone.trim().is_empty(),
+            "invalid form settings {}: the time zone is empty",
+            filename.display()
+        );
+
+        Ok(config)
+    }
+}
+
 pub fn read<P: AsRef<Path>>(
     filename: P,
     replace_with: Vec<(&str, &str)>,
 ) -> anyhow::Result<Vec<QuestionConfig>> {
     let file = File::open(filename)?;
     let mut rdr = csv::Reader::from_reader(file);
+    let headers = rdr.headers()?.clone();
     let mut out: Vec<QuestionConfig> = Vec::new();
 
     for result in rdr.records() {
         let record = result?;
-        let mut question_config = record.deserialize::<QuestionConfig>(None)?;
+        let mut question_config = record.deserialize::<QuestionConfig>(Some(&headers))?;
 
         question_config.fill_template(&replace_with);
+
+        if let ResponseKind::Choice | ResponseKind::Checkbox = question_config.response_kind {
+            ensure!(
+                !question_config.options().is_empty(),
+                "`{}` has no options",
+                question_config.template_raw
+            );
+        }
+
+        let (scale_min, scale_max) = question_config.scale();
+        ensure!(
+            scale_min < scale_max,
+            "invalid scale of `{}`: {} to {}",
+            question_config.template_raw,
+            scale_min,
+            scale_max
+        );
+
         out.push(question_config);
     }
 
     Ok(out)
 }
 
+// Extracts the question id from a form item title, i.e. `Question text [comm-1]`
+pub fn question_id(input: &str) -> Option<&str> {
+    let input = input.trim_end();
+    if !input.ends_with(']') {
+        return None;
+    }
+
+    let start = input.rfind('[')?;
+    let id = input[start + 1..input.len() - 1].trim();
+
+    if id.is_empty() {
+        None
+    } else {
+        Some(id)
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct QuestionConfig {
+    // Optional stable id of the question.
+    // It is appended to the form item title, so that it ends up in the response sheet header.
+    #[serde(default)]
+    pub id: Option<String>,
+
     pub assessment_kind: String,
+
+    // Optional title or id of the response sheet that collects the answers of the assessment kind
+    #[serde(default)]
+    pub sheet: Option<String>,
+
     pub response_kind: ResponseKind,
     pub category: String,
 
-    #[serde(rename = "template")]
+    #[serde(rename = "Template")]
     pub template_raw: String,
 
     #[serde(skip_deserializing)]
     pub template_final: String,
     pub weight: f32,
+
+    // Optional grade bounds and their labels, the defaults apply to the empty ones
+    #[serde(default)]
+    pub scale_min: Option<f32>,
+    #[serde(default)]
+    pub scale_max: Option<f32>,
+    #[serde(default)]
+    pub low_label: Option<String>,
+    #[serde(default)]
+    pub high_label: Option<String>,
+
+    // Options of the `choice` and `checkbox` questions, separated by `|`
+    #[serde(default)]
+    pub options: Option<String>,
+
+    // Optional help text and question order of the category page, see `generate --layout category`
+    #[serde(default)]
+    pub category_description: Option<String>,
+    #[serde(default)]
+    pub shuffle: Option<bool>,
 }
 
 impl QuestionConfig {
@@ -102,17 +323,213 @@ impl QuestionConfig {
         self.template_final = self.template_raw.clone();
         for (from, to) in replacers {
             self.template_final = self.template_final.replace(from, to);
+            if let Some(description) = self.category_description.as_mut() {
+                *description = description.replace(from, to);
+            }
         }
     }
 
     pub fn eval_answer(&self, input: &str) -> Result<String, Box<dyn std_err>> {
         match self.response_kind {
-            ResponseKind::Grade => Ok((input.parse::<f32>()? * self.weight).to_string()),
-            ResponseKind::Text | ResponseKind::Discriminator => Ok(input.to_string()),
+            ResponseKind::Grade | ResponseKind::Numeric | ResponseKind::Nps => {
+                Ok(input.parse::<f32>()?.to_string())
+            }
+            ResponseKind::Text
+            | ResponseKind::Discriminator
+            | ResponseKind::Choice
+            | ResponseKind::Checkbox => Ok(input.to_string()),
+        }
+    }
+
+    // Form item title: the question text followed by the id, if configured
+    pub fn title(&self) -> String {
+        match self.id {
+            Some(ref id) => format!("{} [{}]", self.template_final, id),
+            None => self.template_final.clone(),
+        }
+    }
+
+    // Lowest and highest grade of the question
+    pub fn scale(&self) -> (f32, f32) {
+        if self.response_kind == ResponseKind::Nps {
+            return NPS_SCALE;
         }
+
+        (
+            self.scale_min.unwrap_or(DEFAULT_SCALE_MIN),
+            self.scale_max.unwrap_or(DEFAULT_SCALE_MAX),
+        )
+    }
+
+    // Labels of the lowest and highest grade
+    pub fn labels(&self) -> (&str, &str) {
+        let (low, high) = match self.response_kind {
+            ResponseKind::Nps => (NPS_LOW_LABEL, NPS_HIGH_LABEL),
+            _ => (DEFAULT_LOW_LABEL, DEFAULT_HIGH_LABEL),
+        };
+
+        (
+            non_empty(&self.low_label).unwrap_or(low),
+            non_empty(&self.high_label).unwrap_or(high),
+        )
+    }
+
+    pub fn category_description(&self) -> Option<&str> {
+        non_empty(&self.category_description)
+    }
+
+    pub fn options(&self) -> Vec<&str> {
+        self.options
+            .as_deref()
+            .unwrap_or("")
+            .split(OPTION_SEPARATOR)
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .collect()
+    }
+
+    // Whether the question is graded on the default scale with the default labels
+    pub fn has_default_scale(&self) -> bool {
+        self.scale() == (DEFAULT_SCALE_MIN, DEFAULT_SCALE_MAX)
+            && self.labels() == (DEFAULT_LOW_LABEL, DEFAULT_HIGH_LABEL)
+    }
+
+    pub fn match_id(&self, id: &str) -> bool {
+        self.id.as_deref() == Some(id)
     }
 
     pub fn match_template(&self, input: &str) -> bool {
         input.contains(&self.template_final) || input.contains(&self.template_raw)
     }
 }
+
+// Blank cells count as not set
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::survey::Answer;
+
+    #[test]
+    fn extracts_the_question_id_of_the_title() {
+        assert_eq!(question_id("Question text [comm-1]"), Some("comm-1"));
+        assert_eq!(question_id("Question [a] text [ b ]  "), Some("b"));
+        assert_eq!(question_id("Question text"), None);
+        assert_eq!(question_id("Question [comm-1] text"), None);
+        assert_eq!(question_id("Question text []"), None);
+        assert_eq!(question_id("Question text comm-1]"), None);
+    }
+
+    fn question(response_kind: ResponseKind, scale: (Option<f32>, Option<f32>)) -> QuestionConfig {
+        QuestionConfig {
+            id: None,
+            assessment_kind: String::from("Team-Feedback"),
+            sheet: None,
+            response_kind,
+            category: String::from("Communication"),
+            template_raw: String::from("{name} listens"),
+            template_final: String::from("John listens"),
+            weight: 1.0,
+            scale_min: scale.0,
+            scale_max: scale.1,
+            low_label: None,
+            high_label: Some(String::from(" ")),
+            options: None,
+            category_description: None,
+            shuffle: None,
+        }
+    }
+
+    #[test]
+    fn normalizes_grades_to_scores() {
+        assert_eq!(normalize(1.0, (1.0, 5.0)), 0.0);
+        assert_eq!(normalize(4.0, (1.0, 5.0)), 75.0);
+        assert_eq!(normalize(5.0, (1.0, 5.0)), 100.0);
+        assert_eq!(normalize(0.0, (0.0, 4.0)), 0.0);
+        assert!((normalize(8.0, (1.0, 10.0)) - 77.77778).abs() < 0.001);
+    }
+
+    #[test]
+    fn unset_scale_bounds_and_labels_keep_their_defaults() {
+        let default = question(ResponseKind::Grade, (None, None));
+        assert_eq!(default.scale(), (DEFAULT_SCALE_MIN, DEFAULT_SCALE_MAX));
+        assert_eq!(default.labels(), (DEFAULT_LOW_LABEL, DEFAULT_HIGH_LABEL));
+        assert!(default.has_default_scale());
+
+        let custom = question(ResponseKind::Grade, (Some(0.0), None));
+        assert_eq!(custom.scale(), (0.0, DEFAULT_SCALE_MAX));
+        assert!(!custom.has_default_scale());
+
+        let nps = question(ResponseKind::Nps, (Some(1.0), Some(5.0)));
+        assert_eq!(nps.scale(), NPS_SCALE);
+        assert_eq!(nps.labels(), (NPS_LOW_LABEL, NPS_HIGH_LABEL));
+    }
+
+    fn responses(response_kind: ResponseKind, values: &[&str]) -> Responses {
+        let mut responses = Responses::new(
+            response_kind,
+            String::from("Team-Feedback"),
+            String::from("Communication"),
+        );
+        for (i, value) in values.iter().enumerate() {
+            responses.write(Answer {
+                value: value.to_string(),
+                weight: 1.0,
+                grade: None,
+                respondent: i.to_string(),
+            });
+        }
+        responses
+    }
+
+    #[test]
+    fn scores_the_promoters_against_the_detractors() {
+        let ratings = responses(
+            ResponseKind::Nps,
+            &["10", "9", "9", "8", "7", "6", "0", "10", "3", "9"],
+        );
+        assert_eq!(
+            ResponseKind::Nps.process_data(&ratings),
+            Some(String::from(
+                "+20 (50% promoters, 20% passives, 30% detractors)"
+            ))
+        );
+        assert_eq!(
+            ResponseKind::Nps.process_data(&responses(ResponseKind::Nps, &["6", "7"])),
+            Some(String::from(
+                "-50 (0% promoters, 50% passives, 50% detractors)"
+            ))
+        );
+        assert_eq!(
+            ResponseKind::Nps.process_data(&responses(ResponseKind::Nps, &[])),
+            None
+        );
+    }
+
+    #[test]
+    fn counts_the_respondents_per_option() {
+        let choices = responses(
+            ResponseKind::Checkbox,
+            &["Weekly", "Daily\nWeekly", "Never\nDaily\nWeekly", "Never"],
+        );
+        assert_eq!(
+            ResponseKind::Checkbox.process_data(&choices),
+            Some(String::from(
+                "Weekly: 3 (75%)\nDaily: 2 (50%)\nNever: 2 (50%)"
+            ))
+        );
+    }
+
+    #[test]
+    fn splits_the_options_of_the_template() {
+        let mut template = question(ResponseKind::Choice, (None, None));
+        template.options = Some(String::from(" Daily | Weekly ||Never "));
+        assert_eq!(template.options(), vec!["Daily", "Weekly", "Never"]);
+    }
+}
@@ -1,11 +1,21 @@
 use anyhow::anyhow;
 
+use crate::chart;
 use crate::sheets;
+use crate::survey::mapping::SheetMapping;
 use crate::survey::summary::Summary;
 
-use sheets::spreadsheets::{Sheet, SheetProperties};
-use sheets::spreadsheets_batch_update::{AddSheetRequest, Request, SpreadsheetBatchUpdate};
-use sheets::spreadsheets_values::{MajorDimension, SpreadsheetValueRange};
+use sheets::spreadsheets::{
+    DeveloperMetadata, DeveloperMetadataLocation, DeveloperMetadataVisibility, Sheet,
+    SheetProperties, SheetType, Spreadsheet,
+};
+use sheets::spreadsheets_batch_update::{
+    AddSheetRequest, CreateDeveloperMetadataRequest, Request, SpreadsheetBatchUpdate,
+};
+use sheets::spreadsheets_values::{A1Range, MajorDimension, SpreadsheetValueRange};
+
+// Developer metadata key of the generated summary sheet
+const SUMMARY_METADATA_KEY: &str = "esurvey-summary";
 
 pub struct SpreadsheetClient<'a> {
     sheets_client: &'a sheets::Client,
@@ -20,45 +30,126 @@ impl<'a> SpreadsheetClient<'a> {
         }
     }
 
+    // Reads the response sheets selected by the mapping along with their assessment kinds
     pub fn retrieve_sheet_data(
         &self,
         sheet_items: &[Sheet],
         spreadsheet_id: &str,
-    ) -> anyhow::Result<Vec<SpreadsheetValueRange>> {
-        let sheet_titles = retrieve_sheet_titles(sheet_items);
+        mapping: &SheetMapping,
+        generated: &[&str],
+    ) -> anyhow::Result<(Vec<SpreadsheetValueRange>, Vec<Option<String>>)> {
+        let (sheet_titles, sheet_kinds): (Vec<String>, Vec<Option<String>>) = sheet_items
+            .iter()
+            .filter(|sheet| !is_generated(sheet))
+            .filter_map(|sheet| {
+                let properties = &sheet.properties;
+                mapping
+                    .select(
+                        Some(spreadsheet_id),
+                        &properties.title,
+                        properties.sheet_id,
+                        generated,
+                    )
+                    .map(|kind| (properties.title.clone(), kind))
+            })
+            .unzip();
         println!("sheet titles: > {:#?}", &sheet_titles);
 
-        Ok(self
+        if sheet_titles.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let value_ranges = self
             .sheets_client
             .get_batch_values(self.access_token, spreadsheet_id, sheet_titles)?
-            .value_ranges)
+            .value_ranges;
+
+        Ok((value_ranges, sheet_kinds))
     }
 
+    // Appends the summary tables one after another.
+    // Returns the range every table was written to, keyed by the table title.
     pub fn save_summary(
         &self,
         range: &str,
         spreadsheet_id: &str,
-        summary: Summary,
-    ) -> anyhow::Result<()> {
+        summary: &Summary,
+    ) -> anyhow::Result<Vec<(String, A1Range)>> {
+        let mut written = Vec::new();
+
         for rows in summary.generate_rows() {
             let spreadsheet_values = SpreadsheetValueRange {
                 range: range.to_owned(),
                 major_dimension: MajorDimension::Rows,
                 values: rows.rows(),
+                rows: Vec::new(),
             };
 
-            self.sheets_client.append_values(
+            let response = self.sheets_client.append_values(
                 self.access_token,
                 spreadsheet_id.to_owned(),
                 range.to_owned(),
                 &spreadsheet_values,
             )?;
+
+            written.push((
+                rows.title().to_owned(),
+                A1Range::parse(&response.updates.updated_range)?,
+            ));
         }
 
-        Ok(())
+        Ok(written)
+    }
+
+    // Reuses the summary sheet of a previous evaluation, so that `eval` can be rerun as late responses arrive.
+    // The sheet is recognized by its developer metadata, or by title if it was created by hand.
+    // Returns the id and the current title of the sheet.
+    pub fn prepare_summary_sheet(
+        &self,
+        title: &str,
+        spreadsheet: &Spreadsheet,
+    ) -> anyhow::Result<(u64, String)> {
+        let spreadsheet_id = spreadsheet.spreadsheet_id.as_str();
+
+        let tagged = find_tagged_sheet(spreadsheet, SUMMARY_METADATA_KEY);
+        let existing = tagged.or_else(|| {
+            spreadsheet
+                .sheets
+                .iter()
+                .find(|sheet| sheet.properties.title == title)
+        });
+
+        if let Some(sheet) = existing {
+            let sheet_id = sheet
+                .properties
+                .sheet_id
+                .ok_or_else(|| anyhow!("prepare_summary_sheet: sheet_id not available"))?;
+
+            println!(
+                "clearing the existing summary sheet: > {}",
+                sheet.properties.title
+            );
+            self.sheets_client.clear_values(
+                self.access_token,
+                spreadsheet_id.to_owned(),
+                sheet.properties.title.clone(),
+            )?;
+
+            // a sheet found by title is tagged, so it is still found after a rename
+            if tagged.is_none() {
+                self.add_metadata(spreadsheet_id, sheet_id, SUMMARY_METADATA_KEY)?;
+            }
+
+            return Ok((sheet_id, sheet.properties.title.clone()));
+        }
+
+        let sheet_id = self.add_summary_sheet(title, spreadsheet_id)?;
+        self.add_metadata(spreadsheet_id, sheet_id, SUMMARY_METADATA_KEY)?;
+
+        Ok((sheet_id, title.to_owned()))
     }
 
-    pub fn add_summary_sheet(&self, title: &str, spreadsheet_id: &str) -> anyhow::Result<u64> {
+    fn add_summary_sheet(&self, title: &str, spreadsheet_id: &str) -> anyhow::Result<u64> {
         let batch_update = SpreadsheetBatchUpdate {
             requests: vec![Request {
                 add_sheet: Some(AddSheetRequest {
@@ -67,7 +158,7 @@ impl<'a> SpreadsheetClient<'a> {
                         ..Default::default()
                     },
                 }),
-                add_chart: None,
+                ..Default::default()
             }],
             include_spreadsheet_in_response: true,
             response_ranges: Vec::new(),
@@ -92,11 +183,60 @@ impl<'a> SpreadsheetClient<'a> {
             spreadsheet_id
         ))
     }
+
+    // Tags a generated sheet, so that it is found again even if the user renames it
+    pub fn add_metadata(
+        &self,
+        spreadsheet_id: &str,
+        sheet_id: u64,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        let batch_update = SpreadsheetBatchUpdate {
+            requests: vec![Request {
+                create_developer_metadata: Some(CreateDeveloperMetadataRequest {
+                    developer_metadata: DeveloperMetadata {
+                        metadata_key: key.to_owned(),
+                        location: Some(DeveloperMetadataLocation {
+                            sheet_id: Some(sheet_id),
+                            ..Default::default()
+                        }),
+                        visibility: Some(DeveloperMetadataVisibility::Document),
+                        ..Default::default()
+                    },
+                }),
+                ..Default::default()
+            }],
+            include_spreadsheet_in_response: false,
+            response_ranges: Vec::new(),
+            response_include_grid_data: false,
+        };
+
+        self.sheets_client
+            .batch_update_spreadsheet(self.access_token, spreadsheet_id, &batch_update)
+            .map_err(|err| anyhow!("add_metadata: {}", err))?;
+
+        Ok(())
+    }
 }
 
-fn retrieve_sheet_titles(sheet_items: &[Sheet]) -> Vec<String> {
-    sheet_items
+// Finds a generated sheet by its developer metadata key
+pub fn find_tagged_sheet<'a>(spreadsheet: &'a Spreadsheet, key: &str) -> Option<&'a Sheet> {
+    spreadsheet
+        .sheets
         .iter()
-        .map(|sheet| sheet.properties.title.clone())
-        .collect::<Vec<String>>()
+        .find(|sheet| is_tagged(sheet, key))
+}
+
+fn is_tagged(sheet: &Sheet, key: &str) -> bool {
+    sheet
+        .developer_metadata
+        .iter()
+        .any(|metadata| metadata.metadata_key == key)
+}
+
+// Sheets created by `eval` and chart sheets are never read as responses
+fn is_generated(sheet: &Sheet) -> bool {
+    matches!(sheet.properties.sheet_type, Some(SheetType::Object))
+        || is_tagged(sheet, SUMMARY_METADATA_KEY)
+        || is_tagged(sheet, chart::CHART_METADATA_KEY)
 }
@@ -0,0 +1,182 @@
+use serde_derive::{Deserialize, Serialize};
+
+// https://developers.google.com/forms/api/reference/rest/v1/forms
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Form {
+    // Output only. The form ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub form_id: Option<String>,
+
+    // The title and description of the form.
+    pub info: Info,
+
+    // The form's settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<FormSettings>,
+
+    // A list of the form's items, which can include section headers, questions, embedded media, etc.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<Item>,
+
+    // Output only. The revision ID of the form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_id: Option<String>,
+
+    // Output only. The form URI to share with responders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responder_uri: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    // The title of the form which is visible to responders.
+    pub title: String,
+
+    // Output only. The title of the document which is visible in Drive.
+    // It can only be set on creation, it defaults to the `title`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_title: Option<String>,
+
+    // The description of the form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FormSettings {
+    // The setting that determines whether the form collects email addresses from respondents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_collection_type: Option<EmailCollectionType>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EmailCollectionType {
+    // The form doesn't collect email addresses.
+    DoNotCollect,
+
+    // The form collects email addresses automatically based on the account of the signed-in user.
+    Verified,
+
+    // The form collects email addresses using a field that the respondent completes on the form.
+    ResponderInput,
+}
+
+// A single item of the form. Only one kind of item may be set.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    // The item ID. It can be provided on creation, otherwise a new ID is assigned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+
+    // The title of the item.
+    pub title: String,
+
+    // The description of the item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    // Poses a question to the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question_item: Option<QuestionItem>,
+
+    // Starts a new page with a title.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_break_item: Option<PageBreakItem>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionItem {
+    // The displayed question.
+    pub question: Question,
+}
+
+// Only one kind of question may be set
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Question {
+    // Read only. The question ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question_id: Option<String>,
+
+    // Whether the question must be answered in order for a respondent to submit their response.
+    pub required: bool,
+
+    // A respondent can choose a number from a range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_question: Option<ScaleQuestion>,
+
+    // A respondent can enter a free text response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_question: Option<TextQuestion>,
+
+    // A respondent can choose from a pre-defined set of options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choice_question: Option<ChoiceQuestion>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChoiceQuestion {
+    // The type of choice question.
+    #[serde(rename = "type")]
+    pub choice_type: ChoiceType,
+
+    // List of options that a respondent must choose from.
+    pub options: Vec<ChoiceOption>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChoiceType {
+    // Radio buttons: All choices are shown to the user, who can only pick one of them.
+    Radio,
+
+    // Checkboxes: All choices are shown to the user, who can pick any number of them.
+    Checkbox,
+
+    // Drop-down menu: The choices are only shown to the user on demand, otherwise only the current choice is shown.
+    DropDown,
+}
+
+// An option for a Choice question.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ChoiceOption {
+    // The choice as presented to the user.
+    pub value: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleQuestion {
+    // The lowest possible value for the scale, either 0 or 1.
+    pub low: i32,
+
+    // The highest possible value for the scale, from 2 up to 10.
+    pub high: i32,
+
+    // The label to display describing the lowest point on the scale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_label: Option<String>,
+
+    // The label to display describing the highest point on the scale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high_label: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TextQuestion {
+    // Whether the question is a paragraph question or not.
+    // If not, the question is a short text question.
+    pub paragraph: bool,
+}
+
+// A page break. The title and description of this item are shown at the top of the new page.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct PageBreakItem {}
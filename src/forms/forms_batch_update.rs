@@ -0,0 +1,71 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::form::{Form, FormSettings, Info, Item};
+
+// https://developers.google.com/forms/api/reference/rest/v1/forms/batchUpdate
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateFormRequest {
+    // Whether to return an updated version of the model in the response.
+    pub include_form_in_response: bool,
+
+    // The update requests of this batch.
+    pub requests: Vec<Request>,
+}
+
+// Only one kind of request may be set
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_form_info: Option<UpdateFormInfoRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_settings: Option<UpdateSettingsRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_item: Option<CreateItemRequest>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateFormInfoRequest {
+    // The info to update.
+    pub info: Info,
+
+    // Only values named in this mask are changed, i.e. `description`.
+    pub update_mask: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSettingsRequest {
+    // The settings to update with.
+    pub settings: FormSettings,
+
+    // Only values named in this mask are changed, i.e. `emailCollectionType`.
+    pub update_mask: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateItemRequest {
+    // The item to create.
+    pub item: Item,
+
+    // Where to place the new item.
+    pub location: Location,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    // The index of an item in the form.
+    pub index: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateFormResponse {
+    // Based on the bool request field `include_form_in_response`,
+    // a form with all applied mutations/updates is returned or not.
+    pub form: Option<Form>,
+}
@@ -0,0 +1,124 @@
+use anyhow::{anyhow, ensure};
+use serde_derive::Deserialize;
+
+pub mod form;
+use form::Form;
+
+pub mod forms_batch_update;
+
+pub struct Client {
+    _http_client: reqwest::Client,
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Client {
+            _http_client: reqwest::Client::new(),
+        }
+    }
+
+    // https://developers.google.com/forms/api/reference/rest/v1/forms/create
+    // POST https://forms.googleapis.com/v1/forms
+    //
+    // Only the title is used on creation, the items are added with `batch_update_form`
+    pub fn create_form<S: AsRef<str>>(&self, token: S, form: &Form) -> anyhow::Result<Form> {
+        let url = format!(
+            "https://forms.googleapis.com/v1/forms?access_token={}",
+            token.as_ref()
+        );
+
+        let mut resp = self
+            ._http_client
+            .post(url.as_str())
+            .body(serde_json::to_vec(form)?)
+            .send()?;
+
+        ensure!(resp.status().is_success(), resp.text()?);
+        Ok(resp.json::<Form>()?)
+    }
+
+    // https://developers.google.com/forms/api/reference/rest/v1/forms/batchUpdate
+    // POST https://forms.googleapis.com/v1/forms/{formId}:batchUpdate
+    pub fn batch_update_form<S: AsRef<str>>(
+        &self,
+        token: S,
+        form_id: S,
+        req: &forms_batch_update::BatchUpdateFormRequest,
+    ) -> anyhow::Result<forms_batch_update::BatchUpdateFormResponse> {
+        let url = format!(
+            "https://forms.googleapis.com/v1/forms/{}:batchUpdate?access_token={}",
+            form_id.as_ref(),
+            token.as_ref()
+        );
+
+        let mut resp = self
+            ._http_client
+            .post(url.as_str())
+            .body(serde_json::to_vec(req)?)
+            .send()?;
+
+        ensure!(resp.status().is_success(), resp.text()?);
+        Ok(resp.json::<forms_batch_update::BatchUpdateFormResponse>()?)
+    }
+
+    // https://developers.google.com/drive/api/v3/reference/files/update
+    // PATCH https://www.googleapis.com/drive/v3/files/{fileId}
+    //
+    // New forms are created in the root folder of the drive
+    pub fn move_to_folder<S: AsRef<str>>(
+        &self,
+        token: S,
+        form_id: S,
+        folder_id: S,
+    ) -> anyhow::Result<()> {
+        let url = format!(
+            "https://www.googleapis.com/drive/v3/files/{}?addParents={}&removeParents=root&access_token={}",
+            form_id.as_ref(),
+            folder_id.as_ref(),
+            token.as_ref()
+        );
+
+        let mut resp = self
+            ._http_client
+            .patch(url.as_str())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body("{}")
+            .send()?;
+
+        ensure!(resp.status().is_success(), resp.text()?);
+        Ok(())
+    }
+}
+
+// Where to find a generated form, also returned by the `createForm` script
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FormLinks {
+    pub form_id: String,
+    pub responder_url: String,
+    pub edit_url: String,
+}
+
+impl FormLinks {
+    pub fn new(form: &Form) -> anyhow::Result<Self> {
+        let form_id = form
+            .form_id
+            .clone()
+            .ok_or_else(|| anyhow!("could not retrieve form_id"))?;
+
+        Ok(FormLinks {
+            responder_url: form
+                .responder_uri
+                .clone()
+                .unwrap_or_else(|| format!("https://docs.google.com/forms/d/{}/viewform", form_id)),
+            edit_url: format!("https://docs.google.com/forms/d/{}/edit", form_id),
+            form_id,
+        })
+    }
+
+    pub fn print(&self) {
+        println!("> form id: {}", self.form_id);
+        println!("> responder url: {}", self.responder_url);
+        println!("> edit url: {}", self.edit_url);
+    }
+}
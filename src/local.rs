@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::sheets::spreadsheets_values::{MajorDimension, SpreadsheetValueRange};
+
+// Reads local csv exports of the form responses.
+// The exports are row-major: the header holds the question text and every
+// following row is a single respondent. The data is transposed into columns
+// to match what `sheets::Client::get_batch_values` returns for `majorDimension=COLUMNS`.
+pub fn read_sheet_data<P: AsRef<Path>>(files: &[P]) -> anyhow::Result<Vec<SpreadsheetValueRange>> {
+    let mut value_ranges = Vec::with_capacity(files.len());
+
+    for filename in files {
+        let filename = filename.as_ref();
+        println!("reading file: > {}", filename.display());
+
+        let rows = read_rows(filename)?;
+
+        value_ranges.push(SpreadsheetValueRange {
+            range: sheet_title(filename)?,
+            major_dimension: MajorDimension::Columns,
+            values: transpose(rows),
+            rows: Vec::new(),
+        });
+    }
+
+    Ok(value_ranges)
+}
+
+fn read_rows(filename: &Path) -> anyhow::Result<Vec<Vec<String>>> {
+    let file = File::open(filename)?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(file);
+
+    let mut rows = Vec::new();
+    for result in rdr.records() {
+        let record = result?;
+        rows.push(record.iter().map(String::from).collect::<Vec<String>>());
+    }
+
+    Ok(rows)
+}
+
+fn sheet_title(filename: &Path) -> anyhow::Result<String> {
+    filename
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("invalid file name: {}", filename.display()))
+}
+
+// The Sheets API omits trailing empty cells of a column, so do the same here.
+fn transpose(rows: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut columns: Vec<Vec<String>> = vec![Vec::with_capacity(rows.len()); width];
+
+    for row in rows {
+        let mut cells = row.into_iter();
+        for column in columns.iter_mut() {
+            column.push(cells.next().unwrap_or_default());
+        }
+    }
+
+    for column in columns.iter_mut() {
+        while column.last().map(String::as_str) == Some("") {
+            column.pop();
+        }
+    }
+
+    columns
+}
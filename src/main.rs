@@ -9,6 +9,10 @@ mod chart;
 mod cmd;
 mod config;
 mod drive;
+mod forms;
+mod local;
+mod output;
+mod render;
 mod sheets;
 mod survey;
 
@@ -0,0 +1,17 @@
+use std::io::Write;
+
+use crate::survey::summary::Summary;
+
+// Writes the same rows as the `Chart and Summary` sheet
+pub fn write<W: Write>(summary: &Summary, w: W) -> anyhow::Result<()> {
+    let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(w);
+
+    for rows in summary.generate_rows() {
+        for row in rows.rows() {
+            wtr.write_record(row)?;
+        }
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
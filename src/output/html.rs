@@ -0,0 +1,214 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+
+use anyhow::ensure;
+
+use crate::render::{self, escape};
+use crate::survey::summary::{Summary, SummaryRows, GRADES_TITLE, RATER_GROUPS_TITLE};
+use crate::survey::summary::{REVIEWS_TITLE, VALIDATION_TITLE};
+
+// Placeholders of the report template
+const TITLE: &str = "{title}";
+const STYLE: &str = "{style}";
+const CONTENT: &str = "{content}";
+
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+{style}
+</style>
+</head>
+<body>
+<main>
+<h1>{title}</h1>
+{content}
+</main>
+</body>
+</html>
+"#;
+
+const DEFAULT_STYLE: &str = r#"body { margin: 0; background: #f5f5f5; color: #202124; font-family: "DejaVu Sans", Arial, sans-serif; }
+main { max-width: 1000px; margin: 0 auto; padding: 24px 32px; background: white; }
+h1 { border-bottom: 3px solid #4285f4; padding-bottom: 8px; }
+h2 { margin-top: 40px; color: #4285f4; }
+table { border-collapse: collapse; margin: 12px 0; }
+th, td { border: 1px solid #dddddd; padding: 6px 10px; text-align: left; }
+td { white-space: pre-line; }
+thead th { background: #e8f0fe; }
+tbody th { font-weight: normal; color: #5f6368; }
+figure { margin: 16px 0; }
+figure svg { max-width: 100%; height: auto; }
+.comments li { margin-bottom: 8px; white-space: pre-line; }
+"#;
+
+// Layout of the report: the template wraps the rendered sections
+#[derive(Debug, Clone)]
+pub struct HtmlConfig {
+    pub template: String,
+}
+
+impl Default for HtmlConfig {
+    fn default() -> Self {
+        HtmlConfig {
+            template: DEFAULT_TEMPLATE.to_owned(),
+        }
+    }
+}
+
+impl HtmlConfig {
+    // A custom template has to place the `{content}`, the built-in stylesheet is inserted at `{style}`
+    pub fn set_template_file(&mut self, filename: &str) -> anyhow::Result<()> {
+        let template = fs::read_to_string(filename)?;
+        ensure!(
+            template.contains(CONTENT),
+            "report template {} has no {} placeholder",
+            filename,
+            CONTENT
+        );
+
+        // the report is handed out as a single file, remote assets may not be reachable
+        if template.contains("http://") || template.contains("https://") {
+            println!(
+                "warning: report template {} refers to remote resources, the report may not render offline",
+                filename
+            );
+        }
+
+        self.template = template;
+        Ok(())
+    }
+}
+
+// Writes a standalone page: the charts are inlined as SVG, the styles are embedded
+pub fn write<W: Write>(
+    summary: &Summary,
+    name: &str,
+    config: &HtmlConfig,
+    mut w: W,
+) -> anyhow::Result<()> {
+    let title = format!("Evaluation Summary: {}", name);
+    let mut content = String::new();
+
+    let (scores, details): (Vec<SummaryRows>, Vec<SummaryRows>) = summary
+        .generate_rows()
+        .into_iter()
+        // the reviews are listed as comments, the validation is meant for the evaluator
+        .filter(|rows| rows.title() != REVIEWS_TITLE && rows.title() != VALIDATION_TITLE)
+        .partition(|rows| rows.title() == GRADES_TITLE || rows.title() == RATER_GROUPS_TITLE);
+
+    for rows in scores.iter() {
+        write_table(&mut content, rows);
+    }
+
+    let charts = render::draw_charts(summary, name);
+    if !charts.is_empty() {
+        content.push_str("<section class=\"charts\">\n<h2>Charts</h2>\n");
+        for (kind, svg) in charts {
+            let _ = writeln!(content, "<figure class=\"{}\">\n{}</figure>", kind, svg);
+        }
+        content.push_str("</section>\n");
+    }
+
+    for rows in details.iter() {
+        write_table(&mut content, rows);
+    }
+
+    write_comments(&mut content, summary);
+
+    let page = config
+        .template
+        .replace(STYLE, DEFAULT_STYLE)
+        .replace(TITLE, &escape(&title))
+        .replace(CONTENT, &content);
+
+    w.write_all(page.as_bytes())?;
+    Ok(())
+}
+
+fn write_table(html: &mut String, rows: &SummaryRows) {
+    let mut table = rows.rows().into_iter();
+    let header = match table.next() {
+        Some(header) => header,
+        None => return,
+    };
+
+    let _ = writeln!(
+        html,
+        "<section>\n<h2>{}</h2>\n<table>",
+        escape(rows.title())
+    );
+    html.push_str("<thead><tr>");
+    for cell in header {
+        let _ = write!(html, "<th>{}</th>", escape(&cell));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+
+    for row in table {
+        html.push_str("<tr>");
+        for (i, cell) in row.iter().enumerate() {
+            // the first cell is the row label
+            let tag = if i == 0 { "th" } else { "td" };
+            let _ = write!(html, "<{tag}>{}</{tag}>", escape(cell), tag = tag);
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</tbody>\n</table>\n</section>\n");
+}
+
+// Text answers grouped by category, then by assessment kind, in the order of the summary
+fn write_comments(html: &mut String, summary: &Summary) {
+    let mut categories: Vec<&str> = Vec::new();
+    for responses in summary.texts() {
+        if !categories.contains(&responses.category_name.as_str()) {
+            categories.push(&responses.category_name);
+        }
+    }
+
+    let mut sections = String::new();
+    for category in categories {
+        let mut kinds = String::new();
+        for responses in summary
+            .texts()
+            .iter()
+            .filter(|responses| responses.category_name == category)
+        {
+            let answers = responses
+                .read()
+                .iter()
+                .map(|answer| answer.value.trim())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<&str>>();
+            if answers.is_empty() {
+                continue;
+            }
+
+            let _ = writeln!(
+                kinds,
+                "<h4>{}</h4>\n<ul class=\"comments\">",
+                escape(&responses.assessment_kind)
+            );
+            for answer in answers {
+                let _ = writeln!(kinds, "<li>{}</li>", escape(answer));
+            }
+            kinds.push_str("</ul>\n");
+        }
+
+        if !kinds.is_empty() {
+            let _ = write!(sections, "<h3>{}</h3>\n{}", escape(category), kinds);
+        }
+    }
+
+    if !sections.is_empty() {
+        let _ = write!(
+            html,
+            "<section>\n<h2>Comments</h2>\n{}</section>\n",
+            sections
+        );
+    }
+}
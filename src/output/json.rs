@@ -0,0 +1,9 @@
+use std::io::Write;
+
+use crate::survey::summary::Summary;
+
+pub fn write<W: Write>(summary: &Summary, mut w: W) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(&mut w, &summary.document())?;
+    writeln!(w)?;
+    Ok(())
+}
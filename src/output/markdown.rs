@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use crate::survey::summary::Summary;
+
+// Writes a report with one table per response kind
+pub fn write<W: Write>(summary: &Summary, mut w: W) -> anyhow::Result<()> {
+    writeln!(w, "# Evaluation Summary")?;
+
+    for rows in summary.generate_rows() {
+        let mut table = rows.rows().into_iter();
+        let header = match table.next() {
+            Some(header) => header,
+            None => continue,
+        };
+
+        writeln!(w, "\n## {}\n", rows.title())?;
+
+        write_row(&mut w, &header)?;
+        write_row(&mut w, &vec![String::from("---"); header.len()])?;
+
+        for row in table {
+            write_row(&mut w, &row)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_row<W: Write>(w: &mut W, cells: &[String]) -> anyhow::Result<()> {
+    let escaped = cells
+        .iter()
+        .map(|cell| escape(cell))
+        .collect::<Vec<String>>();
+    writeln!(w, "| {} |", escaped.join(" | "))?;
+    Ok(())
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', "<br>")
+}
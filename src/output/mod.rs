@@ -0,0 +1,71 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+
+use crate::survey::summary::Summary;
+
+mod csv;
+mod html;
+mod json;
+mod markdown;
+
+pub use html::HtmlConfig;
+
+// Destination format of the evaluation summary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    // A `Chart and Summary` tab in the evaluated spreadsheet
+    Sheet,
+    Csv,
+    Json,
+    Markdown,
+    // A standalone page per evaluated person
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sheet" => Ok(Format::Sheet),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "markdown" | "md" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(anyhow!("unknown output format: {}", s)),
+        }
+    }
+}
+
+// Renders the summary of `name` to a local file, or to stdout if no file is provided
+pub fn write(
+    format: Format,
+    summary: &Summary,
+    name: &str,
+    html: &HtmlConfig,
+    filename: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut w: Box<dyn Write> = match filename {
+        Some(filename) => Box::new(File::create(filename)?),
+        None => Box::new(io::stdout()),
+    };
+
+    match format {
+        Format::Csv => csv::write(summary, &mut w)?,
+        Format::Json => json::write(summary, &mut w)?,
+        Format::Markdown => markdown::write(summary, &mut w)?,
+        Format::Html => html::write(summary, name, html, &mut w)?,
+        Format::Sheet => bail!("`sheet` output is written through the Sheets API"),
+    };
+
+    w.flush()?;
+
+    if let Some(filename) = filename {
+        println!("> summary saved to: {}", filename);
+    }
+
+    Ok(())
+}
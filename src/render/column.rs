@@ -0,0 +1,94 @@
+use std::fmt::Write as _;
+
+use super::{color, grid, legend, open, text};
+use crate::survey::summary::ChartSeries;
+
+const WIDTH: u32 = 900;
+const HEIGHT: u32 = 560;
+
+const LEFT: f32 = 50.0;
+const RIGHT: f32 = 700.0;
+const TOP: f32 = 50.0;
+const BOTTOM: f32 = 400.0;
+
+// Grouped column chart: one group per category, one column per series
+pub fn draw(chart: &ChartSeries, name: &str, scale_max: f32) -> String {
+    let mut svg = String::new();
+    open(&mut svg, WIDTH, HEIGHT, name);
+
+    let y = |score: f32| BOTTOM - (BOTTOM - TOP) * score / scale_max;
+
+    // value axis with a grid line per step
+    for step in grid(scale_max) {
+        let top = y(step);
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#dddddd"/>"##,
+            LEFT, top, RIGHT, top
+        );
+        text(
+            &mut svg,
+            LEFT - 8.0,
+            top + 4.0,
+            11,
+            "end",
+            "",
+            &step.to_string(),
+        );
+    }
+
+    let group_width = (RIGHT - LEFT) / chart.categories.len() as f32;
+    let column_width = group_width * 0.8 / chart.series.len() as f32;
+
+    for (category_pos, category) in chart.categories.iter().enumerate() {
+        let group_left = LEFT + group_width * category_pos as f32 + group_width * 0.1;
+
+        for (series_pos, (_, scores)) in chart.series.iter().enumerate() {
+            let score = match scores[category_pos] {
+                Some(score) => score,
+                None => continue,
+            };
+
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{:.1}</title></rect>"#,
+                group_left + column_width * series_pos as f32,
+                y(score),
+                column_width,
+                BOTTOM - y(score),
+                color(series_pos),
+                score
+            );
+        }
+
+        // long category lists are slanted to keep the labels apart
+        let label_x = LEFT + group_width * (category_pos as f32 + 0.5);
+        if chart.categories.len() > 6 {
+            text(
+                &mut svg,
+                label_x,
+                BOTTOM + 16.0,
+                11,
+                "end",
+                &format!(
+                    r#"transform="rotate(-35 {:.1} {:.1})""#,
+                    label_x,
+                    BOTTOM + 16.0
+                ),
+                category,
+            );
+        } else {
+            text(&mut svg, label_x, BOTTOM + 18.0, 12, "middle", "", category);
+        }
+    }
+
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#333333"/>"##,
+        LEFT, BOTTOM, RIGHT, BOTTOM
+    );
+
+    legend(&mut svg, chart, RIGHT + 30.0, TOP);
+    svg.push_str("</svg>\n");
+    svg
+}
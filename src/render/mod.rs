@@ -0,0 +1,193 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use resvg::{tiny_skia, usvg};
+
+use crate::config;
+use crate::survey::summary::{ChartSeries, Summary};
+
+mod column;
+mod radar;
+
+// Series colors, matching the default palette of Google Sheets charts
+const PALETTE: [&str; 8] = [
+    "#4285f4", "#ea4335", "#fbbc04", "#34a853", "#ff6d01", "#46bdc6", "#7baaf7", "#f07b72",
+];
+
+const FONT_FAMILY: &str = "DejaVu Sans, Arial, sans-serif";
+
+// Distance of the grid lines, in score points
+const GRID_STEP: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl FromStr for ImageFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(anyhow!("unknown chart format: {}", s)),
+        }
+    }
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+// Renders the column chart of the summary, as drawn in the spreadsheet,
+// and the radar chart into `dir`, i.e. `john-columns.svg` and `john-radar.png`
+pub fn write_charts(
+    summary: &Summary,
+    dir: &str,
+    name: &str,
+    formats: &[ImageFormat],
+) -> anyhow::Result<()> {
+    let images = draw_charts(summary, name);
+    if images.is_empty() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(dir)?;
+    for (kind, svg) in images {
+        for format in formats {
+            let path = Path::new(dir).join(format!(
+                "{}-{}.{}",
+                name.to_lowercase(),
+                kind,
+                format.extension()
+            ));
+
+            match format {
+                ImageFormat::Svg => fs::write(&path, &svg)?,
+                ImageFormat::Png => write_png(&svg, &path)?,
+            }
+            println!("> charts: {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+// SVG documents of the summary charts by kind, i.e. `columns` and `radar`
+pub fn draw_charts(summary: &Summary, name: &str) -> Vec<(&'static str, String)> {
+    let chart = summary.chart_series();
+    if chart.is_empty() {
+        println!("> charts: no grades to draw");
+        return Vec::new();
+    }
+
+    let scale_max = scale_max(&chart);
+    let mut images = vec![("columns", column::draw(&chart, name, scale_max))];
+
+    // a radar needs at least three axes to enclose an area
+    if chart.categories.len() >= 3 {
+        images.push(("radar", radar::draw(&chart, name, scale_max)));
+    } else {
+        println!("> charts: the radar chart needs at least 3 categories");
+    }
+
+    images
+}
+
+fn write_png(svg: &str, path: &Path) -> anyhow::Result<()> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| anyhow!("can't allocate a {}x{} image", size.width(), size.height()))?;
+
+    pixmap.fill(tiny_skia::Color::WHITE);
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.save_png(path)?;
+
+    Ok(())
+}
+
+// The axis covers the score scale, unless a weighted score exceeds it
+fn scale_max(chart: &ChartSeries) -> f32 {
+    let max = chart
+        .series
+        .iter()
+        .flat_map(|(_, scores)| scores.iter().flatten())
+        .fold(config::SCORE_MAX, |max, score| max.max(*score));
+
+    (max / GRID_STEP).ceil() * GRID_STEP
+}
+
+// Values of the grid lines up to `scale_max`
+fn grid(scale_max: f32) -> impl Iterator<Item = f32> {
+    (0..=(scale_max / GRID_STEP) as u32).map(|step| step as f32 * GRID_STEP)
+}
+
+fn color(index: usize) -> &'static str {
+    PALETTE[index % PALETTE.len()]
+}
+
+// Escapes text for HTML and SVG markup
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn text(svg: &mut String, x: f32, y: f32, size: u32, anchor: &str, extra: &str, content: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" font-family="{}" font-size="{}" text-anchor="{}" {}>{}</text>"#,
+        x,
+        y,
+        FONT_FAMILY,
+        size,
+        anchor,
+        extra,
+        escape(content)
+    );
+}
+
+fn open(svg: &mut String, width: u32, height: u32, title: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{}" height="{}" fill="white"/>"#,
+        width, height
+    );
+    text(svg, width as f32 / 2.0, 28.0, 18, "middle", "", title);
+}
+
+// Legend entries stacked from the top right corner
+fn legend(svg: &mut String, chart: &ChartSeries, x: f32, y: f32) {
+    for (index, (title, _)) in chart.series.iter().enumerate() {
+        let top = y + index as f32 * 22.0;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" fill="{}"/>"#,
+            x,
+            top,
+            color(index)
+        );
+        text(svg, x + 18.0, top + 11.0, 12, "start", "", title);
+    }
+}
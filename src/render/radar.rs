@@ -0,0 +1,91 @@
+use std::f32::consts::PI;
+use std::fmt::Write as _;
+
+use super::{color, grid, legend, open, text};
+use crate::survey::summary::ChartSeries;
+
+const WIDTH: u32 = 960;
+const HEIGHT: u32 = 600;
+
+const CENTER_X: f32 = 420.0;
+const CENTER_Y: f32 = 320.0;
+const RADIUS: f32 = 220.0;
+
+// Spider chart: one axis per category, one polygon per series.
+// Missing scores are drawn at the center.
+pub fn draw(chart: &ChartSeries, name: &str, scale_max: f32) -> String {
+    let mut svg = String::new();
+    open(&mut svg, WIDTH, HEIGHT, name);
+
+    let axes = chart.categories.len();
+    let point = |axis: usize, score: f32| {
+        // the first axis points up, the rest follow clockwise
+        let angle = 2.0 * PI * axis as f32 / axes as f32 - PI / 2.0;
+        let radius = RADIUS * score / scale_max;
+        (
+            CENTER_X + radius * angle.cos(),
+            CENTER_Y + radius * angle.sin(),
+        )
+    };
+    let polygon = |scores: &mut dyn Iterator<Item = f32>| {
+        scores
+            .enumerate()
+            .map(|(axis, score)| {
+                let (x, y) = point(axis, score);
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    // a web ring per step
+    for step in grid(scale_max).skip(1) {
+        let _ = writeln!(
+            svg,
+            r##"<polygon points="{}" fill="none" stroke="#dddddd"/>"##,
+            polygon(&mut (0..axes).map(|_| step))
+        );
+        let (x, y) = point(0, step);
+        text(
+            &mut svg,
+            x + 4.0,
+            y + 4.0,
+            10,
+            "start",
+            r##"fill="#888888""##,
+            &step.to_string(),
+        );
+    }
+
+    for (axis, category) in chart.categories.iter().enumerate() {
+        let (x, y) = point(axis, scale_max);
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#bbbbbb"/>"##,
+            CENTER_X, CENTER_Y, x, y
+        );
+
+        let (label_x, label_y) = point(axis, scale_max * 1.1);
+        let anchor = if (label_x - CENTER_X).abs() < 1.0 {
+            "middle"
+        } else if label_x > CENTER_X {
+            "start"
+        } else {
+            "end"
+        };
+        text(&mut svg, label_x, label_y + 4.0, 12, anchor, "", category);
+    }
+
+    for (series_pos, (_, scores)) in chart.series.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<polygon points="{}" fill="{c}" fill-opacity="0.2" stroke="{c}" stroke-width="2"/>"#,
+            polygon(&mut scores.iter().map(|score| score.unwrap_or(0.0))),
+            c = color(series_pos)
+        );
+    }
+
+    legend(&mut svg, chart, 780.0, 60.0);
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -122,7 +122,7 @@ impl Client {
         spreadsheet_id: String,
         range: String,
         v: &spreadsheets_values::SpreadsheetValueRange,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<spreadsheets_values::AppendValuesResponse> {
         let url = format!(
             "https://sheets.googleapis.com/v4/spreadsheets/{}/values/{}:append?access_token={}&valueInputOption=USER_ENTERED",
             spreadsheet_id,
@@ -136,6 +136,27 @@ impl Client {
             .body(serde_json::to_vec(v)?)
             .send()?;
 
+        ensure!(resp.status().is_success(), resp.text()?);
+        Ok(resp.json::<spreadsheets_values::AppendValuesResponse>()?)
+    }
+
+    // https://developers.google.com/sheets/api/reference/rest/v4/spreadsheets.values/clear
+    // POST https://sheets.googleapis.com/v4/spreadsheets/{spreadsheetId}/values/{range}:clear
+    pub fn clear_values<S: AsRef<str>>(
+        &self,
+        token: S,
+        spreadsheet_id: String,
+        range: String,
+    ) -> anyhow::Result<()> {
+        let url = format!(
+            "https://sheets.googleapis.com/v4/spreadsheets/{}/values/{}:clear?access_token={}",
+            spreadsheet_id,
+            range,
+            token.as_ref(),
+        );
+
+        let mut resp = self._http_client.post(url.as_str()).body("{}").send()?;
+
         ensure!(resp.status().is_success(), resp.text()?);
         Ok(())
     }
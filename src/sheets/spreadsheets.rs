@@ -65,6 +65,29 @@ pub struct IterativeCalculationSettings {}
 #[serde(rename_all = "camelCase")]
 pub struct Sheet {
     pub properties: SheetProperties,
+
+    #[serde(default)]
+    pub charts: Vec<SheetChart>,
+
+    #[serde(default)]
+    pub developer_metadata: Vec<DeveloperMetadata>,
+}
+
+// Subset of the `EmbeddedChart` that identifies an existing chart.
+// The spec is not read back, as only a part of it is modelled.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetChart {
+    pub chart_id: u64,
+
+    #[serde(default)]
+    pub spec: SheetChartSpec,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetChartSpec {
+    pub title: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Default)]
@@ -107,9 +130,34 @@ pub struct GridProperties {
 #[serde(rename_all = "camelCase")]
 pub struct NamedRange {}
 
-#[derive(Deserialize, Serialize, Debug)]
+// https://developers.google.com/sheets/api/reference/rest/v4/spreadsheets.developerMetadata
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DeveloperMetadata {
+    pub metadata_id: Option<u64>,
+    pub metadata_key: String,
+    pub metadata_value: Option<String>,
+    pub location: Option<DeveloperMetadataLocation>,
+    pub visibility: Option<DeveloperMetadataVisibility>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
-pub struct DeveloperMetadata {}
+pub struct DeveloperMetadataLocation {
+    // The ID of the sheet when metadata is associated with an entire sheet.
+    pub sheet_id: Option<u64>,
+    // True when metadata is associated with an entire spreadsheet.
+    pub spreadsheet: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeveloperMetadataVisibility {
+    // Document-visible metadata is accessible from any developer project with access to the document.
+    Document,
+    // Project-visible metadata is only visible to and accessible by the developer project that created the metadata.
+    Project,
+}
 
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
@@ -9,11 +9,18 @@ pub struct SpreadsheetBatchUpdate {
     pub response_include_grid_data: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+// Only one kind of request may be set
+#[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub add_sheet: Option<AddSheetRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub add_chart: Option<AddChartRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_chart_spec: Option<UpdateChartSpecRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_developer_metadata: Option<CreateDeveloperMetadataRequest>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -27,6 +34,23 @@ pub struct AddChartRequest {
     pub chart: super::spreadsheets::EmbeddedChart,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+/// Updates a chart's specifications.
+pub struct UpdateChartSpecRequest {
+    /// The ID of the chart to update.
+    pub chart_id: u64,
+    /// The specification to apply to the chart.
+    pub spec: super::spreadsheets::ChartSpec,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+/// A request to create developer metadata.
+pub struct CreateDeveloperMetadataRequest {
+    #[serde(rename = "developerMetadata")]
+    pub developer_metadata: super::spreadsheets::DeveloperMetadata,
+}
+
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 /// If successful, the response body contains data with the following structure:
@@ -1,3 +1,4 @@
+use anyhow::anyhow;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -13,6 +14,10 @@ pub struct SpreadsheetValueRange {
     pub range: String,
     pub major_dimension: MajorDimension,
     pub values: Vec<Vec<String>>,
+
+    // Spreadsheet rows of the responses, kept through the filter, the dedup and the shuffle
+    #[serde(skip)]
+    pub rows: Vec<usize>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -21,3 +26,120 @@ pub enum MajorDimension {
     Columns,
     Rows,
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendValuesResponse {
+    pub spreadsheet_id: String,
+    pub table_range: Option<String>,
+    pub updates: UpdateValuesResponse,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateValuesResponse {
+    pub spreadsheet_id: String,
+    // The range (in A1 notation) that updates were applied to.
+    pub updated_range: String,
+    pub updated_rows: Option<u64>,
+    pub updated_columns: Option<u64>,
+    pub updated_cells: Option<u64>,
+}
+
+// Zero-based, end-exclusive bounds of an A1 notation range, as used by `GridRange`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct A1Range {
+    pub start_row: u64,
+    pub end_row: u64,
+    pub start_column: u64,
+    pub end_column: u64,
+}
+
+impl A1Range {
+    // Parses a bounded range, i.e. `'Chart and Summary'!A1:D3` or `Sheet1!B2`
+    pub fn parse(range: &str) -> anyhow::Result<Self> {
+        let cells = match range.rfind('!') {
+            Some(pos) => &range[pos + 1..],
+            None => range,
+        };
+
+        let mut parts = cells.splitn(2, ':');
+        let start = parts.next().unwrap_or("");
+        let end = parts.next().unwrap_or(start);
+
+        let (start_column, start_row) =
+            parse_cell(start).ok_or_else(|| anyhow!("invalid A1 range: {}", range))?;
+        let (end_column, end_row) =
+            parse_cell(end).ok_or_else(|| anyhow!("invalid A1 range: {}", range))?;
+
+        Ok(A1Range {
+            start_row,
+            end_row: end_row + 1,
+            start_column,
+            end_column: end_column + 1,
+        })
+    }
+}
+
+// Zero-based column and row of a cell, i.e. `AA3` -> (26, 2)
+fn parse_cell(cell: &str) -> Option<(u64, u64)> {
+    let split = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = cell.split_at(split);
+    if letters.is_empty() {
+        return None;
+    }
+
+    let mut column = 0;
+    for c in letters.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        column = column * 26 + (c.to_ascii_uppercase() as u64 - 'A' as u64 + 1);
+    }
+
+    let row = digits.parse::<u64>().ok().filter(|row| *row > 0)?;
+    Some((column - 1, row - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bounded_ranges() {
+        assert_eq!(
+            A1Range::parse("'Chart and Summary'!A1:D3").unwrap(),
+            A1Range {
+                start_row: 0,
+                end_row: 3,
+                start_column: 0,
+                end_column: 4,
+            }
+        );
+        assert_eq!(
+            A1Range::parse("Sheet1!B2").unwrap(),
+            A1Range {
+                start_row: 1,
+                end_row: 2,
+                start_column: 1,
+                end_column: 2,
+            }
+        );
+        assert_eq!(
+            A1Range::parse("'a!b'!aa10:AB12").unwrap(),
+            A1Range {
+                start_row: 9,
+                end_row: 12,
+                start_column: 26,
+                end_column: 28,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unbounded_ranges() {
+        for range in ["Sheet1!A:D", "Sheet1!1:3", "Sheet1!A0", "Sheet1!", "A1:B-2"].iter() {
+            assert!(A1Range::parse(range).is_err(), "{}", range);
+        }
+    }
+}
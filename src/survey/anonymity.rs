@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use serde_derive::Serialize;
+
+use crate::config::ResponseKind;
+use crate::survey::rater::{RaterConfig, RaterGroup};
+use crate::survey::{Answer, Responses};
+
+// Bucket of the responses of groups with too few respondents
+pub const OTHERS: &str = "Others";
+
+// Minimum number of respondents a group needs for its responses to be published
+#[derive(Debug, Clone)]
+pub struct AnonymityConfig {
+    pub min_respondents: usize,
+
+    // Thresholds by assessment kind or discriminator value, in lowercase.
+    // Overrides the global threshold.
+    pub per_group: HashMap<String, usize>,
+
+    // Self-assessment and the manager are not anonymous, so they are exempt unless set in `per_group`
+    pub raters: RaterConfig,
+}
+
+impl Default for AnonymityConfig {
+    fn default() -> Self {
+        AnonymityConfig {
+            min_respondents: 2,
+            per_group: HashMap::new(),
+            raters: RaterConfig::default(),
+        }
+    }
+}
+
+impl AnonymityConfig {
+    fn threshold(&self, group: &str) -> usize {
+        if let Some(threshold) = self.per_group.get(&group.to_lowercase()) {
+            return *threshold;
+        }
+
+        match self.raters.group(group) {
+            Some(RaterGroup::Myself) | Some(RaterGroup::Manager) => 1,
+            _ => self.min_respondents,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SuppressionKind {
+    // The responses are pooled in the `Others` bucket
+    Merged,
+    // The responses are not published at all
+    Withheld,
+}
+
+impl Display for SuppressionKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SuppressionKind::Merged => write!(f, "merged into {}", OTHERS),
+            SuppressionKind::Withheld => write!(f, "withheld"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Suppression {
+    pub response_kind: ResponseKind,
+    pub assessment_kind: String,
+    pub category: String,
+    pub respondents: usize,
+    pub kind: SuppressionKind,
+}
+
+impl Display for Suppression {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} - {}: {} respondent(s), {}",
+            self.assessment_kind, self.response_kind, self.category, self.respondents, self.kind
+        )
+    }
+}
+
+// The pooled responses of the groups below their threshold in a category
+struct Bucket {
+    responses: Responses,
+    groups: usize,
+    // the highest threshold of the pooled groups
+    threshold: usize,
+}
+
+impl Bucket {
+    // A single group is not hidden by renaming it, the bucket has to pool several
+    fn publishable(&self) -> bool {
+        self.groups > 1 && self.responses.respondents() >= self.threshold
+    }
+}
+
+// Pools the responses of groups below their threshold into the `Others` bucket per category.
+// The bucket is published if it pools at least two groups and meets the threshold of each of them,
+// otherwise the responses are withheld.
+pub fn apply(
+    responses: Vec<Responses>,
+    config: &AnonymityConfig,
+) -> (Vec<Responses>, Vec<Suppression>) {
+    let mut published: Vec<Responses> = Vec::with_capacity(responses.len());
+    let mut below: Vec<Responses> = Vec::new();
+
+    for r in responses {
+        if r.respondents() >= config.threshold(&r.assessment_kind) {
+            published.push(r);
+        } else {
+            below.push(r);
+        }
+    }
+
+    let mut others: Vec<Bucket> = Vec::new();
+    for r in &below {
+        let pos = match others.iter().position(|o| {
+            o.responses.response_kind == r.response_kind
+                && o.responses.category_name == r.category_name
+        }) {
+            Some(pos) => pos,
+            None => {
+                others.push(Bucket {
+                    responses: Responses::new(
+                        r.response_kind.clone(),
+                        String::from(OTHERS),
+                        r.category_name.clone(),
+                    ),
+                    groups: 0,
+                    threshold: 0,
+                });
+                others.len() - 1
+            }
+        };
+
+        let bucket = &mut others[pos];
+        bucket.groups += 1;
+        bucket.threshold = bucket.threshold.max(config.threshold(&r.assessment_kind));
+        for answer in r.read() {
+            bucket.responses.write(Answer::clone(answer));
+        }
+    }
+
+    let mut suppressions: Vec<Suppression> = Vec::with_capacity(below.len());
+    for r in &below {
+        let merged = others.iter().any(|o| {
+            o.responses.response_kind == r.response_kind
+                && o.responses.category_name == r.category_name
+                && o.publishable()
+        });
+
+        suppressions.push(Suppression {
+            response_kind: r.response_kind.clone(),
+            assessment_kind: r.assessment_kind.clone(),
+            category: r.category_name.clone(),
+            respondents: r.respondents(),
+            kind: if merged {
+                SuppressionKind::Merged
+            } else {
+                SuppressionKind::Withheld
+            },
+        });
+    }
+
+    published.extend(
+        others
+            .into_iter()
+            .filter(Bucket::publishable)
+            .map(|o| o.responses),
+    );
+
+    (published, suppressions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::survey::Grade;
+
+    fn responses(kind: &str, category: &str, respondents: &[&str]) -> Responses {
+        let mut responses =
+            Responses::new(ResponseKind::Grade, kind.to_owned(), category.to_owned());
+        for respondent in respondents {
+            responses.write(Answer {
+                value: String::from("50"),
+                weight: 1.0,
+                grade: Some(Grade {
+                    value: 3.0,
+                    scale: (1.0, 5.0),
+                }),
+                respondent: respondent.to_string(),
+            });
+        }
+        responses
+    }
+
+    fn published(responses: &[Responses]) -> Vec<(&str, &str, usize)> {
+        responses
+            .iter()
+            .map(|r| {
+                (
+                    r.assessment_kind.as_str(),
+                    r.category_name.as_str(),
+                    r.respondents(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn publishes_groups_at_the_threshold() {
+        let (kept, suppressions) = apply(
+            vec![
+                responses("Team", "Communication", &["a", "b"]),
+                responses("Team", "Leadership", &["a", "a"]),
+            ],
+            &AnonymityConfig::default(),
+        );
+
+        assert_eq!(published(&kept), vec![("Team", "Communication", 2)]);
+        assert_eq!(suppressions.len(), 1);
+        assert_eq!(suppressions[0].category, "Leadership");
+        assert_eq!(suppressions[0].kind, SuppressionKind::Withheld);
+    }
+
+    #[test]
+    fn pools_small_groups_into_others() {
+        let (kept, suppressions) = apply(
+            vec![
+                responses("Team", "Communication", &["a", "b", "c"]),
+                responses("Report", "Communication", &["r"]),
+                responses("Peer", "Communication", &["p"]),
+                responses("Peer", "Leadership", &["p"]),
+            ],
+            &AnonymityConfig::default(),
+        );
+
+        assert_eq!(
+            published(&kept),
+            vec![("Team", "Communication", 3), (OTHERS, "Communication", 2)]
+        );
+        let kinds = suppressions
+            .iter()
+            .map(|s| (s.assessment_kind.as_str(), s.category.as_str(), s.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Report", "Communication", SuppressionKind::Merged),
+                ("Peer", "Communication", SuppressionKind::Merged),
+                ("Peer", "Leadership", SuppressionKind::Withheld),
+            ]
+        );
+    }
+
+    #[test]
+    fn self_assessment_is_exempt_unless_configured() {
+        let mut config = AnonymityConfig::default();
+        let (kept, suppressions) = apply(
+            vec![responses("Self-Assessment", "Communication", &["me"])],
+            &config,
+        );
+        assert_eq!(
+            published(&kept),
+            vec![("Self-Assessment", "Communication", 1)]
+        );
+        assert!(suppressions.is_empty());
+
+        config.per_group.insert(String::from("self-assessment"), 2);
+        let (kept, suppressions) = apply(
+            vec![responses("Self-Assessment", "Communication", &["me"])],
+            &config,
+        );
+        assert!(kept.is_empty());
+        assert_eq!(suppressions[0].kind, SuppressionKind::Withheld);
+    }
+
+    #[test]
+    fn a_single_manager_is_published() {
+        let (kept, suppressions) = apply(
+            vec![responses("Manager", "Communication", &["m"])],
+            &AnonymityConfig::default(),
+        );
+
+        assert_eq!(published(&kept), vec![("Manager", "Communication", 1)]);
+        assert!(suppressions.is_empty());
+    }
+
+    #[test]
+    fn per_group_thresholds_override_the_global_one() {
+        let mut config = AnonymityConfig::default();
+        config.per_group.insert(String::from("manager"), 2);
+        config.per_group.insert(String::from("team"), 3);
+
+        let (kept, suppressions) = apply(
+            vec![
+                responses("Manager", "Communication", &["m"]),
+                responses("Team", "Communication", &["a", "b"]),
+            ],
+            &config,
+        );
+
+        assert_eq!(published(&kept), vec![(OTHERS, "Communication", 3)]);
+        assert!(suppressions
+            .iter()
+            .all(|suppression| suppression.kind == SuppressionKind::Merged));
+
+        let (kept, suppressions) = apply(
+            vec![responses("Team", "Communication", &["a", "b"])],
+            &config,
+        );
+        assert!(kept.is_empty());
+        assert_eq!(suppressions[0].kind, SuppressionKind::Withheld);
+    }
+
+    #[test]
+    fn others_meet_the_highest_threshold_of_the_pooled_groups() {
+        let mut config = AnonymityConfig::default();
+        config.per_group.insert(String::from("peer"), 4);
+
+        let (kept, _) = apply(
+            vec![
+                responses("Peer", "Communication", &["a", "b"]),
+                responses("Report", "Communication", &["c"]),
+                responses("Peer", "Leadership", &["a", "b", "c"]),
+                responses("Report", "Leadership", &["d"]),
+            ],
+            &config,
+        );
+
+        assert_eq!(published(&kept), vec![(OTHERS, "Leadership", 4)]);
+    }
+}
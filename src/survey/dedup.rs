@@ -0,0 +1,430 @@
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::config::QuestionConfig;
+use crate::sheets::spreadsheets_values::SpreadsheetValueRange;
+use crate::survey::{
+    pad_columns, respondent_count, sheet_title,
+    timestamp::{self, DateOrder},
+    FIRST_QUESTION_COLUMN, RESPONDENT_COLUMN, TIMESTAMP_COLUMN,
+};
+
+// Which submission is kept if a respondent answered an assessment kind more than once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupPolicy {
+    Latest,
+    First,
+    // Grades are averaged, other answers are taken from the latest submission
+    Average,
+}
+
+impl FromStr for DedupPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "latest" => Ok(DedupPolicy::Latest),
+            "first" => Ok(DedupPolicy::First),
+            "average" => Ok(DedupPolicy::Average),
+            _ => Err(anyhow!("unknown dedup policy: {}", s)),
+        }
+    }
+}
+
+// Number of submissions removed per sheet
+#[derive(Debug, Clone, Default)]
+pub struct DedupReport {
+    pub removed: Vec<(String, usize)>,
+}
+
+impl DedupReport {
+    pub fn total(&self) -> usize {
+        self.removed.iter().map(|(_, removed)| removed).sum()
+    }
+
+    pub fn print(&self) {
+        if self.total() == 0 {
+            println!("> dedup: no duplicate submissions found");
+            return;
+        }
+
+        eprintln!("> dedup: {} duplicate submission(s) removed", self.total());
+        for (sheet, removed) in self.removed.iter().filter(|(_, removed)| *removed > 0) {
+            eprintln!(">   {}: {}", sheet, removed);
+        }
+    }
+}
+
+// Key of the sheets whose submissions are deduplicated together: the assessment kind a sheet is
+// mapped to, otherwise the assessment kind of the first question it asks. A resubmission to a merged
+// spreadsheet or to a regenerated form is found in another sheet of the same assessment kind.
+pub fn sheet_groups(
+    sheets: &[SpreadsheetValueRange],
+    sheet_kinds: &[Option<String>],
+    templates: &[QuestionConfig],
+) -> Vec<String> {
+    sheets
+        .iter()
+        .enumerate()
+        .map(|(position, sheet)| {
+            let asked = || {
+                sheet
+                    .values
+                    .iter()
+                    .skip(FIRST_QUESTION_COLUMN)
+                    .filter_map(|column| column.first())
+                    .find_map(|header| {
+                        templates
+                            .iter()
+                            .find(|template| template.match_template(header))
+                    })
+                    .map(|template| template.assessment_kind.clone())
+            };
+
+            match sheet_kinds.get(position).cloned().flatten().or_else(asked) {
+                Some(kind) => kind.to_lowercase(),
+                // sheets without known questions are deduplicated on their own
+                None => format!("#{}", position),
+            }
+        })
+        .collect()
+}
+
+// A submission by its sheet and its row in the sheet data, the header is row 0
+type Submission = (usize, usize);
+
+// Keeps a single submission per respondent and group of sheets.
+// Respondents are matched by email, so the identifiers must not be stripped yet.
+// Submissions are ordered by timestamp, or by their sheet and row if a timestamp can not be parsed.
+pub fn dedup(
+    sheets: &mut [SpreadsheetValueRange],
+    groups: &[String],
+    policy: DedupPolicy,
+    date_order: DateOrder,
+) -> DedupReport {
+    for sheet in sheets.iter_mut() {
+        pad_columns(sheet);
+    }
+
+    let mut removed: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); sheets.len()];
+    let mut seen: Vec<&str> = Vec::new();
+    for group in groups {
+        if seen.contains(&group.as_str()) {
+            continue;
+        }
+        seen.push(group);
+
+        let members = (0..sheets.len())
+            .filter(|&position| groups.get(position) == Some(group))
+            .collect::<Vec<usize>>();
+
+        for duplicates in submissions(sheets, &members, date_order)
+            .into_iter()
+            .filter(|duplicates| duplicates.len() > 1)
+        {
+            let kept = match policy {
+                DedupPolicy::First => duplicates[0],
+                DedupPolicy::Latest | DedupPolicy::Average => duplicates[duplicates.len() - 1],
+            };
+
+            if policy == DedupPolicy::Average {
+                for (column, grade) in average(sheets, &duplicates, kept) {
+                    sheets[kept.0].values[column][kept.1] = grade;
+                }
+            }
+
+            for &(sheet, row) in duplicates.iter().filter(|&&submission| submission != kept) {
+                removed[sheet].insert(row);
+            }
+        }
+    }
+
+    let mut report = DedupReport::default();
+    for (sheet, removed) in sheets.iter_mut().zip(removed.iter()) {
+        report
+            .removed
+            .push((sheet_title(&sheet.range).to_owned(), removed.len()));
+        if removed.is_empty() {
+            continue;
+        }
+
+        let respondents = respondent_count(sheet);
+        for column in sheet.values.iter_mut() {
+            let mut row = 0;
+            column.retain(|_| {
+                row += 1;
+                !removed.contains(&(row - 1))
+            });
+        }
+
+        if sheet.rows.len() == respondents {
+            let mut row = 0;
+            sheet.rows.retain(|_| {
+                row += 1;
+                !removed.contains(&row)
+            });
+        }
+    }
+
+    report
+}
+
+// Submissions of the sheets grouped by respondent, in the order of the first submission.
+// The submissions of every respondent are sorted from the first to the latest one.
+fn submissions(
+    sheets: &[SpreadsheetValueRange],
+    members: &[usize],
+    date_order: DateOrder,
+) -> Vec<Vec<Submission>> {
+    let mut submissions: Vec<Vec<Submission>> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+
+    for &position in members {
+        let sheet = &sheets[position];
+        let ids = sheet.values.get(RESPONDENT_COLUMN);
+
+        for row in 1..=respondent_count(sheet) {
+            let id = ids
+                .and_then(|ids| ids.get(row))
+                .map(|id| id.trim().to_lowercase())
+                .unwrap_or_default();
+
+            // responses without an email address can't be related
+            if id.is_empty() {
+                submissions.push(vec![(position, row)]);
+                continue;
+            }
+
+            match by_id.get(&id) {
+                Some(&pos) => submissions[pos].push((position, row)),
+                None => {
+                    by_id.insert(id, submissions.len());
+                    submissions.push(vec![(position, row)]);
+                }
+            }
+        }
+    }
+
+    for duplicates in submissions
+        .iter_mut()
+        .filter(|duplicates| duplicates.len() > 1)
+    {
+        let parsed = duplicates
+            .iter()
+            .map(|&(sheet, row)| {
+                sheets[sheet]
+                    .values
+                    .get(TIMESTAMP_COLUMN)
+                    .and_then(|timestamps| timestamp::parse(&timestamps[row], date_order))
+            })
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(parsed) = parsed {
+            let mut ordered = duplicates.iter().cloned().zip(parsed).collect::<Vec<_>>();
+            ordered.sort_by_key(|&(submission, timestamp)| (timestamp, submission));
+            *duplicates = ordered
+                .into_iter()
+                .map(|(submission, _)| submission)
+                .collect();
+        }
+    }
+
+    submissions
+}
+
+// Mean grades of the kept submission's questions, the answers of the other sheets are found by
+// the question header. Questions with non numeric answers keep the answer of the kept submission.
+fn average(
+    sheets: &[SpreadsheetValueRange],
+    duplicates: &[Submission],
+    kept: Submission,
+) -> Vec<(usize, String)> {
+    let kept_sheet = &sheets[kept.0];
+    let mut averages = Vec::new();
+
+    for (column, values) in kept_sheet
+        .values
+        .iter()
+        .enumerate()
+        .skip(FIRST_QUESTION_COLUMN)
+    {
+        let header = match values.first() {
+            Some(header) => header.trim(),
+            None => continue,
+        };
+
+        let answers = duplicates
+            .iter()
+            .filter_map(|&(sheet, row)| {
+                sheets[sheet]
+                    .values
+                    .iter()
+                    .skip(FIRST_QUESTION_COLUMN)
+                    .find(|values| matches!(values.first(), Some(h) if h.trim() == header))
+                    .and_then(|values| values.get(row))
+            })
+            .map(|answer| answer.trim())
+            .filter(|answer| !answer.is_empty())
+            .collect::<Vec<&str>>();
+
+        let grades = answers
+            .iter()
+            .map(|answer| answer.parse::<f32>().ok())
+            .collect::<Option<Vec<f32>>>();
+
+        if let Some(grades) = grades.filter(|grades| !grades.is_empty()) {
+            averages.push((
+                column,
+                (grades.iter().sum::<f32>() / grades.len() as f32).to_string(),
+            ));
+        }
+    }
+
+    averages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheets::spreadsheets_values::MajorDimension;
+    use crate::survey::number_rows;
+
+    // Column major sheet of the rows, the first row is the header
+    fn sheet(title: &str, rows: &[&[&str]]) -> SpreadsheetValueRange {
+        let mut values = vec![Vec::new(); rows[0].len()];
+        for row in rows {
+            for (column, value) in row.iter().enumerate() {
+                values[column].push(value.to_string());
+            }
+        }
+
+        SpreadsheetValueRange {
+            range: format!("'{}'!A1:Z100", title),
+            major_dimension: MajorDimension::Columns,
+            values,
+            rows: Vec::new(),
+        }
+    }
+
+    fn column(sheet: &SpreadsheetValueRange, column: usize) -> Vec<&str> {
+        sheet.values[column]
+            .iter()
+            .skip(1)
+            .map(String::as_str)
+            .collect()
+    }
+
+    const HEADER: &[&str] = &["Timestamp", "Email Address", "Grade [a]", "Comment [b]"];
+
+    fn team_sheets() -> Vec<SpreadsheetValueRange> {
+        let mut sheets = vec![
+            sheet(
+                "Team",
+                &[
+                    HEADER,
+                    &["1/02/2019 10:00:00", "ann@example.com", "2", "first"],
+                    &["1/02/2019 11:00:00", "bob@example.com", "3", "bob"],
+                    &["1/03/2019 10:00:00", "Ann@example.com ", "4", "second"],
+                ],
+            ),
+            sheet(
+                "Team (2)",
+                &[
+                    HEADER,
+                    &["1/04/2019 10:00:00", "ann@example.com", "5", "third"],
+                    &["1/01/2019 10:00:00", "bob@example.com", "1", "early"],
+                ],
+            ),
+            sheet(
+                "Self",
+                &[
+                    HEADER,
+                    &["1/05/2019 10:00:00", "ann@example.com", "3", "self"],
+                ],
+            ),
+        ];
+        number_rows(&mut sheets);
+        sheets
+    }
+
+    fn groups() -> Vec<String> {
+        vec!["team".to_owned(), "team".to_owned(), "self".to_owned()]
+    }
+
+    #[test]
+    fn keeps_the_latest_submission_across_the_sheets_of_a_group() {
+        let mut sheets = team_sheets();
+        let report = dedup(&mut sheets, &groups(), DedupPolicy::Latest, DateOrder::Auto);
+
+        assert_eq!(report.total(), 3);
+        assert_eq!(column(&sheets[0], 3), vec!["bob"]);
+        assert_eq!(sheets[0].rows, vec![3]);
+        assert_eq!(column(&sheets[1], 3), vec!["third"]);
+        assert_eq!(sheets[1].rows, vec![2]);
+        // other assessment kinds are not affected
+        assert_eq!(column(&sheets[2], 3), vec!["self"]);
+        assert_eq!(sheets[2].rows, vec![2]);
+    }
+
+    #[test]
+    fn keeps_the_first_submission() {
+        let mut sheets = team_sheets();
+        dedup(&mut sheets, &groups(), DedupPolicy::First, DateOrder::Auto);
+
+        assert_eq!(column(&sheets[0], 3), vec!["first"]);
+        assert_eq!(sheets[0].rows, vec![2]);
+        assert_eq!(column(&sheets[1], 3), vec!["early"]);
+        assert_eq!(sheets[1].rows, vec![3]);
+    }
+
+    #[test]
+    fn averages_the_grades_of_the_submissions() {
+        let mut sheets = team_sheets();
+        dedup(
+            &mut sheets,
+            &groups(),
+            DedupPolicy::Average,
+            DateOrder::Auto,
+        );
+
+        assert_eq!(column(&sheets[0], 2), vec!["2"]);
+        assert_eq!(column(&sheets[0], 3), vec!["bob"]);
+        assert_eq!(column(&sheets[1], 2), vec!["3.6666667"]);
+        assert_eq!(column(&sheets[1], 3), vec!["third"]);
+    }
+
+    #[test]
+    fn sheets_of_different_groups_are_deduplicated_on_their_own() {
+        let mut sheets = team_sheets();
+        let groups = vec!["#0".to_owned(), "#1".to_owned(), "#2".to_owned()];
+        let report = dedup(&mut sheets, &groups, DedupPolicy::Latest, DateOrder::Auto);
+
+        assert_eq!(report.total(), 1);
+        assert_eq!(column(&sheets[0], 3), vec!["bob", "second"]);
+        assert_eq!(sheets[0].rows, vec![3, 4]);
+        assert_eq!(column(&sheets[1], 3), vec!["third", "early"]);
+    }
+
+    #[test]
+    fn responses_without_email_are_kept() {
+        let mut sheets = vec![sheet(
+            "Anonymous",
+            &[
+                HEADER,
+                &["1/02/2019 10:00:00", "", "2", "one"],
+                &["1/02/2019 11:00:00", "", "3", "two"],
+            ],
+        )];
+        let report = dedup(
+            &mut sheets,
+            &["#0".to_owned()],
+            DedupPolicy::Latest,
+            DateOrder::Auto,
+        );
+
+        assert_eq!(report.total(), 0);
+        assert_eq!(column(&sheets[0], 3), vec!["one", "two"]);
+    }
+}
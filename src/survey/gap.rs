@@ -0,0 +1,180 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde_derive::Serialize;
+
+use crate::survey::Responses;
+
+// Settings of the self-assessment vs. team-feedback comparison
+#[derive(Debug, Clone)]
+pub struct GapConfig {
+    pub self_kind: String,
+    pub team_kind: String,
+
+    // Minimal absolute difference between self and team scores (of 0 to 100) that is considered a gap
+    pub gap_threshold: f32,
+
+    // Team score starting from which an agreed category is a strength
+    pub strength_threshold: f32,
+}
+
+impl Default for GapConfig {
+    fn default() -> Self {
+        GapConfig {
+            self_kind: String::from("Self-Assessment"),
+            team_kind: String::from("Team-Feedback"),
+            // half a grade and 3.5 of the default 1 to 5 scale
+            gap_threshold: 12.5,
+            strength_threshold: 62.5,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GapKind {
+    // Rated higher by self than by the team
+    BlindSpot,
+    // Rated higher by the team than by self
+    HiddenStrength,
+    AgreedStrength,
+    AgreedWeakness,
+}
+
+impl Display for GapKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GapKind::BlindSpot => write!(f, "blind spot"),
+            GapKind::HiddenStrength => write!(f, "hidden strength"),
+            GapKind::AgreedStrength => write!(f, "agreed strength"),
+            GapKind::AgreedWeakness => write!(f, "agreed weakness"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Gap {
+    pub category: String,
+    pub self_score: f32,
+    pub team_score: f32,
+
+    // Self score minus team score
+    pub delta: f32,
+    pub kind: GapKind,
+}
+
+// Compares self and team scores of every category graded by both
+pub fn analyze(grades: &[Responses], config: &GapConfig) -> Vec<Gap> {
+    let score = |kind: &str, category: &str| -> Option<f32> {
+        grades
+            .iter()
+            .find(|r| r.assessment_kind == kind && r.category_name == category)
+            .and_then(|r| r.weighted_mean())
+    };
+
+    let mut gaps: Vec<Gap> = Vec::new();
+    for responses in grades
+        .iter()
+        .filter(|r| r.assessment_kind == config.self_kind)
+    {
+        let category = &responses.category_name;
+        let (self_score, team_score) = match (
+            score(&config.self_kind, category),
+            score(&config.team_kind, category),
+        ) {
+            (Some(self_score), Some(team_score)) => (self_score, team_score),
+            _ => continue,
+        };
+
+        let delta = self_score - team_score;
+        let kind = if delta >= config.gap_threshold {
+            GapKind::BlindSpot
+        } else if delta <= -config.gap_threshold {
+            GapKind::HiddenStrength
+        } else if team_score >= config.strength_threshold {
+            GapKind::AgreedStrength
+        } else {
+            GapKind::AgreedWeakness
+        };
+
+        gaps.push(Gap {
+            category: category.clone(),
+            self_score,
+            team_score,
+            delta,
+            kind,
+        });
+    }
+
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ResponseKind;
+    use crate::survey::Answer;
+
+    fn graded(kind: &str, category: &str, score: f32) -> Responses {
+        let mut responses =
+            Responses::new(ResponseKind::Grade, kind.to_owned(), category.to_owned());
+        responses.write(Answer {
+            value: score.to_string(),
+            weight: 1.0,
+            grade: None,
+            respondent: kind.to_owned(),
+        });
+        responses
+    }
+
+    #[test]
+    fn classifies_the_categories_graded_by_both() {
+        let mut grades = Vec::new();
+        for (category, self_score, team_score) in [
+            ("Adaptability", 90.0, 70.0),
+            ("Attitude", 50.0, 62.5),
+            ("Communication", 75.0, 70.0),
+            ("Leadership", 40.0, 50.0),
+        ]
+        .iter()
+        {
+            grades.push(graded("Self-Assessment", category, *self_score));
+            grades.push(graded("Team-Feedback", category, *team_score));
+        }
+        // only graded by self
+        grades.push(graded("Self-Assessment", "Teamwork", 100.0));
+
+        let gaps = analyze(&grades, &GapConfig::default())
+            .into_iter()
+            .map(|gap| (gap.category, gap.delta, gap.kind))
+            .collect::<Vec<(String, f32, GapKind)>>();
+
+        assert_eq!(
+            gaps,
+            vec![
+                (String::from("Adaptability"), 20.0, GapKind::BlindSpot),
+                (String::from("Attitude"), -12.5, GapKind::HiddenStrength),
+                (String::from("Communication"), 5.0, GapKind::AgreedStrength),
+                (String::from("Leadership"), -10.0, GapKind::AgreedWeakness),
+            ]
+        );
+    }
+
+    #[test]
+    fn compares_the_configured_kinds() {
+        let grades = vec![
+            graded("Self-Assessment", "Adaptability", 90.0),
+            graded("Me", "Adaptability", 50.0),
+            graded("Peers", "Adaptability", 50.0),
+        ];
+        let config = GapConfig {
+            self_kind: String::from("Me"),
+            team_kind: String::from("Peers"),
+            ..GapConfig::default()
+        };
+
+        let gaps = analyze(&grades, &config);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].kind, GapKind::AgreedWeakness);
+    }
+}
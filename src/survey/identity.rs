@@ -0,0 +1,192 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+
+use crate::sheets::spreadsheets_values::SpreadsheetValueRange;
+use crate::survey::{pad_columns, RESPONDENT_COLUMN};
+
+// Length of the hashed respondent key in hex characters
+const KEY_LEN: usize = 16;
+
+// What is kept of the respondent identifiers, i.e. the `Email Address` column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdentityMode {
+    // Replaced by a salted hash, so that the answers of a respondent can still be related
+    Hash,
+    // Removed completely
+    Drop,
+}
+
+impl FromStr for IdentityMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hash" => Ok(IdentityMode::Hash),
+            "drop" => Ok(IdentityMode::Drop),
+            _ => Err(anyhow!("unknown respondent id mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdentityConfig {
+    pub mode: IdentityMode,
+
+    // Salt of the respondent hashes, also seeds the respondent order.
+    // A random one is generated if not set.
+    pub salt: Option<String>,
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        IdentityConfig {
+            mode: IdentityMode::Hash,
+            salt: None,
+        }
+    }
+}
+
+// Replaces the respondent identifiers of every sheet and shuffles the respondent order,
+// so that neither the identities nor the submission order reach the outputs
+pub fn anonymize(sheets: &mut [SpreadsheetValueRange], config: &IdentityConfig) {
+    let salt = config.salt.clone().unwrap_or_else(random_salt);
+
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&Sha256::digest(format!("shuffle:{}", salt).as_bytes()));
+    let mut rng = StdRng::from_seed(seed);
+
+    for sheet in sheets.iter_mut() {
+        let respondents = pad_columns(sheet);
+
+        if let Some(ids) = sheet.values.get_mut(RESPONDENT_COLUMN) {
+            for id in ids.iter_mut().skip(1) {
+                *id = match config.mode {
+                    IdentityMode::Hash if !id.trim().is_empty() => hash(&salt, id),
+                    _ => String::new(),
+                };
+            }
+        }
+
+        let mut order: Vec<usize> = (1..=respondents).collect();
+        order.shuffle(&mut rng);
+
+        for column in sheet.values.iter_mut() {
+            let shuffled = order
+                .iter()
+                .map(|&row| column[row].clone())
+                .collect::<Vec<String>>();
+
+            column.truncate(1);
+            column.extend(shuffled);
+        }
+
+        if sheet.rows.len() == respondents {
+            sheet.rows = order.iter().map(|&row| sheet.rows[row - 1]).collect();
+        }
+    }
+}
+
+fn hash(salt: &str, id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(id.trim().to_lowercase().as_bytes());
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()[..KEY_LEN]
+        .to_owned()
+}
+
+fn random_salt() -> String {
+    let mut rng = rand::thread_rng();
+    (0..32)
+        .map(|_| format!("{:02x}", rng.gen::<u8>()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheets::spreadsheets_values::MajorDimension;
+    use crate::survey::number_rows;
+
+    fn sheet() -> SpreadsheetValueRange {
+        let respondents = ["ann@example.com", "bob@example.com", "", "eve@example.com"];
+        let mut sheets = vec![SpreadsheetValueRange {
+            range: String::from("'Team'!A1:D5"),
+            major_dimension: MajorDimension::Columns,
+            values: vec![
+                vec!["Timestamp", "1", "2", "3", "4"],
+                [&["Email Address"][..], &respondents[..]].concat(),
+                vec!["Answer [a]", "ann", "bob", "anonymous", "eve"],
+            ]
+            .into_iter()
+            .map(|column| column.into_iter().map(String::from).collect())
+            .collect(),
+            rows: Vec::new(),
+        }];
+        number_rows(&mut sheets);
+        sheets.remove(0)
+    }
+
+    fn config(mode: IdentityMode, salt: &str) -> IdentityConfig {
+        IdentityConfig {
+            mode,
+            salt: Some(salt.to_owned()),
+        }
+    }
+
+    #[test]
+    fn hashes_are_salted_and_case_insensitive() {
+        let key = hash("salt", "Ann@Example.com ");
+
+        assert_eq!(key.len(), KEY_LEN);
+        assert_eq!(key, hash("salt", "ann@example.com"));
+        assert_ne!(key, hash("pepper", "ann@example.com"));
+        assert_ne!(key, hash("salt", "bob@example.com"));
+    }
+
+    #[test]
+    fn replaces_the_ids_and_keeps_the_rows_together() {
+        let mut sheets = vec![sheet()];
+        anonymize(&mut sheets, &config(IdentityMode::Hash, "salt"));
+        let sheet = &sheets[0];
+
+        for (row, timestamp) in sheet.values[0].iter().enumerate().skip(1) {
+            let original: usize = timestamp.parse().unwrap();
+            let expected_id = match original {
+                1 => hash("salt", "ann@example.com"),
+                2 => hash("salt", "bob@example.com"),
+                3 => String::new(),
+                _ => hash("salt", "eve@example.com"),
+            };
+
+            assert_eq!(sheet.values[1][row], expected_id);
+            assert_eq!(
+                sheet.values[2][row],
+                ["ann", "bob", "anonymous", "eve"][original - 1]
+            );
+            assert_eq!(sheet.rows[row - 1], original + 1);
+        }
+        assert!(!sheet.values[1].iter().any(|id| id.contains('@')));
+    }
+
+    #[test]
+    fn the_salt_fixes_the_order() {
+        let order = |salt: &str| {
+            let mut sheets = vec![sheet()];
+            anonymize(&mut sheets, &config(IdentityMode::Drop, salt));
+            sheets.remove(0).values
+        };
+
+        let values = order("salt");
+        assert_eq!(values, order("salt"));
+        assert!(values[1].iter().skip(1).all(String::is_empty));
+        assert_eq!(values[1][0], "Email Address");
+    }
+}
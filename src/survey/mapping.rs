@@ -0,0 +1,152 @@
+use anyhow::bail;
+
+use crate::config::QuestionConfig;
+
+// Maps the response sheets to assessment kinds.
+// A sheet is selected by its title or its numeric id (`gid`), optionally prefixed
+// with the spreadsheet id when several spreadsheets are evaluated: `SPREADSHEET_ID/TITLE`.
+#[derive(Debug, Clone, Default)]
+pub struct SheetMapping {
+    entries: Vec<(String, String)>,
+}
+
+impl SheetMapping {
+    // Parses a `SHEET=KIND` mapping
+    pub fn add(&mut self, input: &str) -> anyhow::Result<()> {
+        let mut parts = input.rsplitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(kind), Some(sheet)) if !kind.trim().is_empty() && !sheet.trim().is_empty() => {
+                self.entries
+                    .push((sheet.trim().to_owned(), kind.trim().to_owned()));
+                Ok(())
+            }
+            _ => bail!("invalid `sheet`: {}, expected SHEET=KIND", input),
+        }
+    }
+
+    // Mappings set on the command line take precedence over the ones of the templates
+    pub fn add_templates(&mut self, templates: &[QuestionConfig]) {
+        for template in templates {
+            if let Some(sheet) = &template.sheet {
+                let exists = self
+                    .entries
+                    .iter()
+                    .any(|(s, _)| s.eq_ignore_ascii_case(sheet.trim()));
+
+                if !exists {
+                    self.entries
+                        .push((sheet.trim().to_owned(), template.assessment_kind.clone()));
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn kind_of(
+        &self,
+        spreadsheet_id: Option<&str>,
+        title: &str,
+        sheet_id: Option<u64>,
+    ) -> Option<&str> {
+        let sheet_id = sheet_id.map(|id| id.to_string());
+        let matches = |selector: &str| {
+            selector.eq_ignore_ascii_case(title) || sheet_id.as_deref() == Some(selector)
+        };
+
+        self.entries
+            .iter()
+            .find(|(selector, _)| {
+                let mut parts = selector.splitn(2, '/');
+                match (parts.next(), parts.next(), spreadsheet_id) {
+                    (Some(spreadsheet), Some(sheet), Some(id)) if spreadsheet == id => {
+                        matches(sheet)
+                    }
+                    _ => matches(selector),
+                }
+            })
+            .map(|(_, kind)| kind.as_str())
+    }
+
+    // Generated tabs are never read. Without a mapping every other sheet is read,
+    // otherwise only the mapped ones.
+    pub fn select(
+        &self,
+        spreadsheet_id: Option<&str>,
+        title: &str,
+        sheet_id: Option<u64>,
+        generated: &[&str],
+    ) -> Option<Option<String>> {
+        if generated
+            .iter()
+            .any(|generated| generated.eq_ignore_ascii_case(title))
+        {
+            return None;
+        }
+
+        match self.kind_of(spreadsheet_id, title, sheet_id) {
+            Some(kind) => Some(Some(kind.to_owned())),
+            None if self.is_empty() => Some(None),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &[&str] = &["Chart and Summary"];
+
+    #[test]
+    fn reads_every_sheet_without_a_mapping() {
+        let mapping = SheetMapping::default();
+
+        assert_eq!(
+            mapping.select(None, "Form Responses 1", Some(0), GENERATED),
+            Some(None)
+        );
+        assert_eq!(
+            mapping.select(None, "chart and summary", Some(7), GENERATED),
+            None
+        );
+    }
+
+    #[test]
+    fn selects_the_mapped_sheets_only() {
+        let mut mapping = SheetMapping::default();
+        mapping.add("team-feedback=Team-Feedback").unwrap();
+        mapping.add("1234=Self-Assessment").unwrap();
+        mapping.add("abc/Form Responses 1=Peer").unwrap();
+
+        let select = |spreadsheet_id, title, sheet_id| {
+            mapping.select(spreadsheet_id, title, sheet_id, GENERATED)
+        };
+        assert_eq!(
+            select(None, "Team-Feedback", Some(1)),
+            Some(Some(String::from("Team-Feedback")))
+        );
+        assert_eq!(
+            select(None, "Form Responses 2", Some(1234)),
+            Some(Some(String::from("Self-Assessment")))
+        );
+        assert_eq!(
+            select(Some("abc"), "Form Responses 1", Some(5)),
+            Some(Some(String::from("Peer")))
+        );
+        assert_eq!(select(Some("xyz"), "Form Responses 1", Some(5)), None);
+        assert_eq!(select(None, "Other", Some(2)), None);
+    }
+
+    #[test]
+    fn mappings_need_a_sheet_and_a_kind() {
+        let mut mapping = SheetMapping::default();
+
+        assert!(mapping.add("team-feedback").is_err());
+        assert!(mapping.add("=Team-Feedback").is_err());
+        assert!(mapping.add("team-feedback= ").is_err());
+        assert!(mapping.is_empty());
+    }
+}
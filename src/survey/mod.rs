@@ -1,129 +1,708 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use anyhow::anyhow;
 
-use crate::config::{QuestionConfig, ResponseKind};
+use crate::config::{self, QuestionConfig, ResponseKind};
 use crate::sheets::spreadsheets_values::SpreadsheetValueRange;
 
+pub mod anonymity;
+pub mod dedup;
+pub mod gap;
+pub mod identity;
+pub mod mapping;
+pub mod rater;
+pub mod stats;
 pub mod summary;
+pub mod timestamp;
+pub mod validation;
+
+use stats::Stats;
+use validation::{IssueKind, Report};
+
+// Column of the submission timestamps
+pub const TIMESTAMP_COLUMN: usize = 0;
+
+// Column of the respondent identifiers, i.e. the `Email Address`
+pub const RESPONDENT_COLUMN: usize = 1;
+
+// The first two columns hold the timestamp and the respondent identifier
+pub const FIRST_QUESTION_COLUMN: usize = 2;
+
+// Header of the respondent column inserted into the sheets of anonymous forms
+const INSERTED_RESPONDENT_HEADER: &str = "";
+
+// A single processed answer along with the weight of its question
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub value: String,
+    pub weight: f32,
+
+    // Grade as answered on the scale of the question, the value holds its score
+    pub grade: Option<Grade>,
+
+    // Identifies the respondent within the evaluation
+    pub respondent: String,
+}
+
+// A grade as answered, along with the scale of its question
+#[derive(Debug, Clone, Copy)]
+pub struct Grade {
+    pub value: f32,
+    pub scale: (f32, f32),
+}
 
 #[derive(Debug)]
 pub struct Responses {
+    pub response_kind: ResponseKind,
     pub assessment_kind: String,
     pub category_name: String,
-    vals: Vec<String>,
+    vals: Vec<Answer>,
 }
 
 impl Responses {
-    fn new(assessment_kind: String, category_name: String) -> Self {
+    pub fn new(
+        response_kind: ResponseKind,
+        assessment_kind: String,
+        category_name: String,
+    ) -> Self {
         Responses {
+            response_kind,
             assessment_kind,
             category_name,
             vals: Vec::new(),
         }
     }
 
-    fn write(&mut self, response: &str) {
-        self.vals.push(String::from(response))
+    pub fn write(&mut self, response: Answer) {
+        self.vals.push(response)
     }
 
-    pub fn read(&self) -> &Vec<String> {
+    pub fn read(&self) -> &[Answer] {
         &self.vals
     }
+
+    // Number of distinct respondents who answered in the category
+    pub fn respondents(&self) -> usize {
+        self.vals
+            .iter()
+            .map(|answer| answer.respondent.as_str())
+            .collect::<BTreeSet<&str>>()
+            .len()
+    }
+
+    // Sum of grade x weight divided by the sum of weights
+    pub fn weighted_mean(&self) -> Option<f32> {
+        let (weighted_sum, weights) = self
+            .grades()
+            .fold((0.0, 0.0), |(sum, weights), (grade, weight)| {
+                (sum + grade * weight, weights + weight)
+            });
+
+        if weights > 0.0 {
+            Some(weighted_sum / weights)
+        } else {
+            None
+        }
+    }
+
+    pub fn mean(&self) -> Option<f32> {
+        if self.vals.is_empty() {
+            return None;
+        }
+
+        let sum = self.grades().map(|(grade, _)| grade).sum::<f32>();
+        Some(sum / self.vals.len() as f32)
+    }
+
+    pub fn stats(&self) -> Option<Stats> {
+        let answered = self
+            .vals
+            .iter()
+            .filter_map(|answer| answer.grade)
+            .collect::<Vec<Grade>>();
+
+        Stats::from_grades(
+            &self.grades().map(|(grade, _)| grade).collect::<Vec<f32>>(),
+            &answered,
+        )
+    }
+
+    // The answers are validated while scanning, so all of them are parsable grades
+    fn grades(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.vals.iter().filter_map(|answer| {
+            answer
+                .value
+                .parse::<f32>()
+                .ok()
+                .map(|grade| (grade, answer.weight))
+        })
+    }
+}
+
+// Groups the answers by assessment kind and category, preserving the scan order
+#[derive(Default)]
+struct Collector {
+    category_map: HashMap<String, Responses>,
+    ord_categories: Vec<String>,
+}
+
+impl Collector {
+    fn write(&mut self, assessement_title: String, template: &QuestionConfig, answer: Answer) {
+        let discriminator: String = format!(
+            "{}:{}:{}",
+            template.response_kind, assessement_title, template.category
+        );
+
+        let ord_categories = &mut self.ord_categories;
+        self.category_map
+            .entry(discriminator.clone())
+            .or_insert_with(|| {
+                ord_categories.push(discriminator);
+                Responses::new(
+                    template.response_kind.clone(),
+                    assessement_title,
+                    template.category.clone(),
+                )
+            })
+            .write(answer);
+    }
+
+    fn into_responses(mut self) -> Vec<Responses> {
+        let mut category_data: Vec<Responses> = Vec::new();
+        for category_name in self.ord_categories {
+            if let Some(scanned) = self.category_map.remove(&category_name) {
+                category_data.push(scanned);
+            }
+        }
+
+        category_data
+    }
+}
+
+fn validate_answer(template: &QuestionConfig, input: &str) -> Result<String, IssueKind> {
+    if input.trim().is_empty() {
+        return Err(IssueKind::EmptyAnswer);
+    }
+
+    let processed_answer = template
+        .eval_answer(input.trim())
+        .map_err(|_| IssueKind::Unparsable)?;
+
+    match template.response_kind {
+        ResponseKind::Grade | ResponseKind::Nps => {
+            let (scale_min, scale_max) = template.scale();
+            let grade = processed_answer
+                .parse::<f32>()
+                .map_err(|_| IssueKind::Unparsable)?;
+
+            if grade < scale_min || grade > scale_max {
+                return Err(IssueKind::OutOfRange);
+            }
+
+            // grades of different scales are aggregated as scores
+            if template.response_kind == ResponseKind::Grade {
+                return Ok(config::normalize(grade, (scale_min, scale_max)).to_string());
+            }
+        }
+        ResponseKind::Choice if !template.options().contains(&processed_answer.as_str()) => {
+            return Err(IssueKind::UnknownOption);
+        }
+        ResponseKind::Checkbox => {
+            let selected = split_options(&processed_answer, &template.options())
+                .ok_or(IssueKind::UnknownOption)?;
+            return Ok(selected.join("\n"));
+        }
+        _ => {}
+    }
+
+    Ok(processed_answer)
+}
+
+// Forms export the checked options joined by `, `, the options themselves may contain commas.
+// Returns `None` if any part of the answer is not an option.
+fn split_options<'a>(answer: &str, options: &[&'a str]) -> Option<Vec<&'a str>> {
+    let mut selected = Vec::new();
+    let mut rest = answer;
+
+    while !rest.is_empty() {
+        let option = options
+            .iter()
+            .filter(|option| {
+                rest.starts_with(**option)
+                    && (rest.len() == option.len() || rest[option.len()..].starts_with(", "))
+            })
+            .max_by_key(|option| option.len())?;
+
+        selected.push(*option);
+        rest = rest[option.len()..].trim_start_matches(", ");
+    }
+
+    Some(selected)
+}
+
+// Extracts the sheet title from an A1 notation range, i.e. `'team-feedback'!A1:Z1000`
+fn sheet_title(range: &str) -> &str {
+    let title = match range.rfind('!') {
+        Some(pos) => &range[..pos],
+        None => range,
+    };
+
+    title.trim_matches('\'')
+}
+
+// Anonymous forms export no `Email Address` column, the answers start right after the timestamp.
+// An empty respondent column is inserted, so the answers of all sheets start at `FIRST_QUESTION_COLUMN`.
+pub fn insert_respondent_columns(
+    sheets: &mut [SpreadsheetValueRange],
+    templates: &[QuestionConfig],
+) {
+    for sheet in sheets.iter_mut() {
+        let header = sheet
+            .values
+            .get(RESPONDENT_COLUMN)
+            .and_then(|column| column.first());
+        if !matches!(header, Some(header) if templates.iter().any(|template| template.match_template(header)))
+        {
+            continue;
+        }
+
+        println!(
+            "> {}: no respondent column, the responses are anonymous",
+            sheet_title(&sheet.range)
+        );
+        sheet.values.insert(
+            RESPONDENT_COLUMN,
+            vec![INSERTED_RESPONDENT_HEADER.to_owned()],
+        );
+        pad_columns(sheet);
+    }
+}
+
+// Column of the sheet as exported, the inserted respondent column aside
+fn sheet_column(sheet: &SpreadsheetValueRange, column: usize) -> usize {
+    match sheet
+        .values
+        .get(RESPONDENT_COLUMN)
+        .and_then(|column| column.first())
+    {
+        Some(header) if header == INSERTED_RESPONDENT_HEADER && column > RESPONDENT_COLUMN => {
+            column - 1
+        }
+        _ => column,
+    }
+}
+
+// Numbers the responses by their spreadsheet row, the header is row 1
+pub fn number_rows(sheets: &mut [SpreadsheetValueRange]) {
+    for sheet in sheets.iter_mut() {
+        let respondents = pad_columns(sheet);
+        sheet.rows = (2..respondents + 2).collect();
+    }
+}
+
+// Spreadsheet row of the response at `index`, as displayed in the spreadsheet
+fn sheet_row(sheet: &SpreadsheetValueRange, index: usize) -> usize {
+    sheet.rows.get(index).copied().unwrap_or(index + 2)
+}
+
+// Number of response rows, the header row excluded.
+// The Sheets API omits trailing empty cells, so the columns may differ in length.
+pub fn respondent_count(sheet: &SpreadsheetValueRange) -> usize {
+    sheet
+        .values
+        .iter()
+        .map(|column| column.len().saturating_sub(1))
+        .max()
+        .unwrap_or(0)
+}
+
+// Pads every column with empty cells up to the number of response rows
+pub fn pad_columns(sheet: &mut SpreadsheetValueRange) -> usize {
+    let respondents = respondent_count(sheet);
+    for column in sheet.values.iter_mut() {
+        column.resize(respondents + 1, String::new());
+    }
+
+    respondents
+}
+
+// Hashed respondent identifier if it was kept, otherwise the position of the response.
+// The sheet is identified by its position, as the titles of merged spreadsheets may repeat.
+fn respondent_key(sheet: &SpreadsheetValueRange, position: usize, index: usize) -> String {
+    match sheet
+        .values
+        .get(RESPONDENT_COLUMN)
+        .and_then(|ids| ids.get(index + 1))
+        .filter(|id| !id.is_empty())
+    {
+        Some(id) => id.clone(),
+        None => format!("{}:{}", position, index),
+    }
 }
 
+// Discriminator answers of a single respondent, keyed by the position of the discriminator template
+type Discriminators = BTreeMap<usize, String>;
+
 pub struct Survey<'a> {
     templates: &'a [QuestionConfig],
+
+    // Categories of the discriminators used to attribute the answers.
+    // All discriminators are used if empty.
+    slice_by: Vec<String>,
+
+    // Assessment kinds of the sheets, in the order of the scanned sheets.
+    // Unmapped sheets are filed under the templates' assessment kinds.
+    sheet_kinds: Vec<Option<String>>,
 }
 
 impl<'a> Survey<'a> {
     pub fn new(templates: &'a [QuestionConfig]) -> Self {
-        Survey { templates }
+        Survey {
+            templates,
+            slice_by: Vec::new(),
+            sheet_kinds: Vec::new(),
+        }
+    }
+
+    pub fn set_slice_by(&mut self, slice_by: Vec<String>) {
+        self.slice_by = slice_by;
+    }
+
+    pub fn set_sheet_kinds(&mut self, sheet_kinds: Vec<Option<String>>) {
+        self.sheet_kinds = sheet_kinds;
     }
 
+    // Scans the column-major sheet data. The invalid cells are skipped and collected in the report
     pub fn scan_all(
         &self,
         from_sheets: &[SpreadsheetValueRange],
-    ) -> anyhow::Result<Vec<Responses>> {
-        let raw_data: Vec<&Vec<String>> = from_sheets
-            .iter()
-            .flat_map(|sheet_raw_data| &sheet_raw_data.values)
-            .collect();
+    ) -> anyhow::Result<(Vec<Responses>, Report)> {
+        let mut collector = Collector::default();
+        let mut report = Report::default();
 
-        self.scan(raw_data)
+        for (position, sheet) in from_sheets.iter().enumerate() {
+            let sheet_kind = self.sheet_kinds.get(position).and_then(Option::as_deref);
+            self.scan(position, sheet, sheet_kind, &mut collector, &mut report)?;
+        }
+
+        Ok((collector.into_responses(), report))
     }
 
-    // todo:
-    //   - optimize against clones
-    //   - discriminator config right now works with the assumption that it is the first entry:
-    //      solution: first collect discriminators, then process responses
-    fn scan(&self, raw_data: Vec<&Vec<String>>) -> anyhow::Result<Vec<Responses>> {
-        let answers = raw_data.into_iter().skip(2);
-        let mut category_map: HashMap<String, Responses> = HashMap::new();
-        let mut ord_categories: Vec<String> = Vec::new();
+    // Discriminators are collected in a separate pass, so that every answer of a respondent
+    // is attributed regardless of the column order
+    fn scan(
+        &self,
+        position: usize,
+        sheet: &SpreadsheetValueRange,
+        sheet_kind: Option<&str>,
+        collector: &mut Collector,
+        report: &mut Report,
+    ) -> anyhow::Result<()> {
+        let sheet_title = sheet_title(&sheet.range);
+        let respondents = respondent_count(sheet);
 
-        let mut discriminators: Vec<String> = Vec::new();
+        let discriminators = self.scan_discriminators(sheet, sheet_kind, respondents, report);
 
-        for answer in answers {
+        for (column, answer) in sheet.values.iter().enumerate().skip(FIRST_QUESTION_COLUMN) {
             let mut per_category = answer.iter();
             let qst_stmt = per_category.next().ok_or_else(|| {
                 anyhow!("error scanning category question in: {:#?}", per_category)
             })?;
 
-            // todo: optimize here
-            let template = match self.find_config_template(qst_stmt) {
-                Some(t) => t,
+            let template = match self.find_config_template(qst_stmt, sheet_kind) {
+                Some((_, t)) => t,
                 None => {
-                    eprintln!("> template not found for: {}", qst_stmt);
+                    report.add(
+                        sheet_title,
+                        1,
+                        sheet_column(sheet, column),
+                        IssueKind::UnmatchedQuestion,
+                        qst_stmt,
+                    );
                     continue;
                 }
             };
 
-            for (index, grade_in) in per_category.enumerate() {
-                let processed_answer = template
-                    .eval_answer(grade_in.as_ref())
-                    .unwrap_or_else(|_| panic!("failed evalling: {}", grade_in));
+            if let ResponseKind::Discriminator = template.response_kind {
+                continue;
+            };
 
-                if let ResponseKind::Discriminator = template.response_kind {
-                    discriminators.push(processed_answer);
-                    continue;
-                };
+            for (index, respondent_discriminators) in discriminators.iter().enumerate() {
+                let grade_in = answer.get(index + 1).map(String::as_str).unwrap_or("");
 
-                let assessement_title: String = match discriminators.get(index) {
-                    Some(t) => t.clone(),
-                    None => template.assessment_kind.clone(),
+                let processed_answer = match validate_answer(template, grade_in) {
+                    Ok(processed_answer) => processed_answer,
+                    Err(issue_kind) => {
+                        let (row, column) = (sheet_row(sheet, index), sheet_column(sheet, column));
+                        report.add(sheet_title, row, column, issue_kind, grade_in);
+                        continue;
+                    }
                 };
 
-                let discriminator: String = format!("{}:{}", assessement_title, template.category);
-
-                category_map
-                    .entry(discriminator.clone())
-                    .and_modify(|ctgr_data| {
-                        ctgr_data.write(&processed_answer);
-                    })
-                    .or_insert_with(|| {
-                        let mut ctgr_data =
-                            Responses::new(assessement_title.clone(), template.category.clone());
-                        ctgr_data.write(&processed_answer);
-                        ord_categories.push(discriminator);
-                        ctgr_data
-                    });
+                collector.write(
+                    self.assessment_title(template, sheet_kind, respondent_discriminators),
+                    template,
+                    Answer {
+                        value: processed_answer,
+                        weight: template.weight,
+                        grade: match template.response_kind {
+                            ResponseKind::Grade => {
+                                grade_in.trim().parse::<f32>().ok().map(|value| Grade {
+                                    value,
+                                    scale: template.scale(),
+                                })
+                            }
+                            _ => None,
+                        },
+                        respondent: respondent_key(sheet, position, index),
+                    },
+                );
             }
         }
 
-        let mut category_data: Vec<Responses> = Vec::new();
-        for category_name in ord_categories {
-            if let Some(scanned) = category_map.remove(&category_name) {
-                category_data.push(scanned);
+        Ok(())
+    }
+
+    fn scan_discriminators(
+        &self,
+        sheet: &SpreadsheetValueRange,
+        sheet_kind: Option<&str>,
+        respondents: usize,
+        report: &mut Report,
+    ) -> Vec<Discriminators> {
+        let sheet_title = sheet_title(&sheet.range);
+        let mut discriminators: Vec<Discriminators> = vec![Discriminators::new(); respondents];
+
+        for (column, answer) in sheet.values.iter().enumerate().skip(FIRST_QUESTION_COLUMN) {
+            let (position, template) = match answer
+                .first()
+                .and_then(|qst_stmt| self.find_config_template(qst_stmt, sheet_kind))
+            {
+                Some((position, t)) if t.response_kind == ResponseKind::Discriminator => {
+                    (position, t)
+                }
+                _ => continue,
+            };
+
+            let sliced = self.slice_by.is_empty()
+                || self
+                    .slice_by
+                    .iter()
+                    .any(|category| category.eq_ignore_ascii_case(&template.category));
+
+            for (index, respondent_discriminators) in discriminators.iter_mut().enumerate() {
+                let value_in = answer.get(index + 1).map(String::as_str).unwrap_or("");
+
+                match validate_answer(template, value_in) {
+                    Ok(value) if sliced => {
+                        respondent_discriminators.insert(position, value);
+                    }
+                    Ok(_) => {}
+                    Err(issue_kind) => {
+                        let (row, column) = (sheet_row(sheet, index), sheet_column(sheet, column));
+                        report.add(sheet_title, row, column, issue_kind, value_in)
+                    }
+                };
             }
         }
 
-        Ok(category_data)
+        discriminators
     }
 
-    fn find_config_template(&self, input_question: &str) -> Option<&QuestionConfig> {
-        self.templates
-            .iter()
-            .find(|tmplt| tmplt.match_template(input_question))
+    // The answers of respondents without discriminators are filed under the sheet's or the template's
+    // assessment kind, otherwise under the discriminator values, i.e. `Peer / Team A`
+    fn assessment_title(
+        &self,
+        template: &QuestionConfig,
+        sheet_kind: Option<&str>,
+        discriminators: &Discriminators,
+    ) -> String {
+        if discriminators.is_empty() {
+            return sheet_kind
+                .map(String::from)
+                .unwrap_or_else(|| template.assessment_kind.clone());
+        }
+
+        discriminators
+            .values()
+            .map(String::as_str)
+            .collect::<Vec<&str>>()
+            .join(" / ")
+    }
+
+    // Matches on the question id first, the text match is only a fallback
+    // for forms generated without ids.
+    // Templates of the sheet's assessment kind are preferred, as the same question may be asked in several forms.
+    fn find_config_template(
+        &self,
+        input_question: &str,
+        sheet_kind: Option<&str>,
+    ) -> Option<(usize, &QuestionConfig)> {
+        let find = |matches: &dyn Fn(&QuestionConfig) -> bool| {
+            let mut found = self
+                .templates
+                .iter()
+                .enumerate()
+                .filter(|(_, tmplt)| matches(tmplt));
+
+            match sheet_kind {
+                Some(kind) => found
+                    .clone()
+                    .find(|(_, tmplt)| tmplt.assessment_kind.eq_ignore_ascii_case(kind))
+                    .or_else(|| found.next()),
+                None => found.next(),
+            }
+        };
+
+        if let Some(id) = config::question_id(input_question) {
+            if let Some(found) = find(&|tmplt| tmplt.match_id(id)) {
+                return Some(found);
+            }
+        }
+
+        find(&|tmplt| tmplt.match_template(input_question))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graded(answers: &[(&str, f32)]) -> Responses {
+        let mut responses = Responses::new(
+            ResponseKind::Grade,
+            String::from("Team-Feedback"),
+            String::from("Communication"),
+        );
+        for (i, (value, weight)) in answers.iter().enumerate() {
+            responses.write(Answer {
+                value: value.to_string(),
+                weight: *weight,
+                grade: None,
+                respondent: i.to_string(),
+            });
+        }
+        responses
+    }
+
+    #[test]
+    fn weighs_the_grades_of_a_category() {
+        let responses = graded(&[("100", 3.0), ("0", 1.0)]);
+
+        assert_eq!(responses.weighted_mean(), Some(75.0));
+        assert_eq!(responses.mean(), Some(50.0));
+        assert_eq!(
+            ResponseKind::Grade.process_data(&responses),
+            Some(String::from("75.0"))
+        );
+        assert_eq!(
+            ResponseKind::Grade.process_grades_unweighted(&responses),
+            Some(String::from("50.0"))
+        );
+    }
+
+    #[test]
+    fn grades_without_weight_have_no_mean() {
+        assert_eq!(graded(&[("100", 0.0)]).weighted_mean(), None);
+        assert_eq!(graded(&[]).weighted_mean(), None);
+        assert_eq!(graded(&[]).mean(), None);
+    }
+
+    fn question(response_kind: ResponseKind, scale: (f32, f32), options: &str) -> QuestionConfig {
+        QuestionConfig {
+            id: None,
+            assessment_kind: String::from("Team-Feedback"),
+            sheet: None,
+            response_kind,
+            category: String::from("Communication"),
+            template_raw: String::from("How does John communicate?"),
+            template_final: String::from("How does John communicate?"),
+            weight: 1.0,
+            scale_min: Some(scale.0),
+            scale_max: Some(scale.1),
+            low_label: None,
+            high_label: None,
+            options: Some(options.to_owned()),
+            category_description: None,
+            shuffle: None,
+        }
+    }
+
+    #[test]
+    fn grades_are_validated_on_the_question_scale() {
+        let template = question(ResponseKind::Grade, (1.0, 10.0), "");
+
+        assert_eq!(validate_answer(&template, " 10 "), Ok(String::from("100")));
+        assert_eq!(validate_answer(&template, "1"), Ok(String::from("0")));
+        assert_eq!(validate_answer(&template, "0"), Err(IssueKind::OutOfRange));
+        assert_eq!(validate_answer(&template, "11"), Err(IssueKind::OutOfRange));
+        assert_eq!(
+            validate_answer(&template, "ten"),
+            Err(IssueKind::Unparsable)
+        );
+        assert_eq!(validate_answer(&template, ""), Err(IssueKind::EmptyAnswer));
+    }
+
+    #[test]
+    fn choices_must_be_options() {
+        let choice = question(ResponseKind::Choice, (1.0, 5.0), "Daily | Weekly|Never");
+        assert_eq!(
+            validate_answer(&choice, "Weekly"),
+            Ok(String::from("Weekly"))
+        );
+        assert_eq!(
+            validate_answer(&choice, "Monthly"),
+            Err(IssueKind::UnknownOption)
+        );
+
+        let checkbox = question(
+            ResponseKind::Checkbox,
+            (1.0, 5.0),
+            "Chat|Mail, letters|Calls",
+        );
+        assert_eq!(
+            validate_answer(&checkbox, "Chat, Mail, letters"),
+            Ok(String::from("Chat\nMail, letters"))
+        );
+        assert_eq!(
+            validate_answer(&checkbox, "Chat, Fax"),
+            Err(IssueKind::UnknownOption)
+        );
+    }
+
+    #[test]
+    fn nps_keeps_the_rating() {
+        let nps = question(ResponseKind::Nps, (1.0, 5.0), "");
+        assert_eq!(validate_answer(&nps, "9"), Ok(String::from("9")));
+        assert_eq!(validate_answer(&nps, "0"), Ok(String::from("0")));
+        assert_eq!(validate_answer(&nps, "11"), Err(IssueKind::OutOfRange));
+
+        let numeric = question(ResponseKind::Numeric, (1.0, 5.0), "");
+        assert_eq!(validate_answer(&numeric, "42.5"), Ok(String::from("42.5")));
+    }
+
+    #[test]
+    fn splits_the_checked_options() {
+        let options = ["Chat", "Mail, letters", "Mail", "Calls"];
+
+        assert_eq!(split_options("Chat", &options), Some(vec!["Chat"]));
+        assert_eq!(
+            split_options("Chat, Mail, letters, Calls", &options),
+            Some(vec!["Chat", "Mail, letters", "Calls"])
+        );
+        assert_eq!(
+            split_options("Mail, Calls", &options),
+            Some(vec!["Mail", "Calls"])
+        );
+        assert_eq!(split_options("", &options), Some(vec![]));
+    }
+
+    #[test]
+    fn rejects_unknown_options() {
+        let options = ["Chat", "Calls"];
+
+        assert_eq!(split_options("Mail", &options), None);
+        assert_eq!(split_options("Chat, Mail", &options), None);
+        assert_eq!(split_options("Chatter", &options), None);
     }
 }
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use serde_derive::Serialize;
+
+use crate::survey::{Answer, Responses};
+
+// Relationship of the respondents to the evaluated person, as in a 360° review
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum RaterGroup {
+    #[serde(rename = "self")]
+    Myself,
+    Manager,
+    Peers,
+    DirectReports,
+    // Everyone except self
+    AllOthers,
+}
+
+// Assessment kinds and discriminator values recognized without a `--rater-group` mapping
+const DEFAULT_GROUPS: [(&str, RaterGroup); 12] = [
+    ("self", RaterGroup::Myself),
+    ("self-assessment", RaterGroup::Myself),
+    ("manager", RaterGroup::Manager),
+    ("team lead", RaterGroup::Manager),
+    ("peer", RaterGroup::Peers),
+    ("peers", RaterGroup::Peers),
+    ("colleague", RaterGroup::Peers),
+    ("colleagues", RaterGroup::Peers),
+    ("direct report", RaterGroup::DirectReports),
+    ("direct reports", RaterGroup::DirectReports),
+    ("subordinate", RaterGroup::DirectReports),
+    ("subordinates", RaterGroup::DirectReports),
+];
+
+impl FromStr for RaterGroup {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "self" => Ok(RaterGroup::Myself),
+            "manager" => Ok(RaterGroup::Manager),
+            "peers" => Ok(RaterGroup::Peers),
+            "direct-reports" => Ok(RaterGroup::DirectReports),
+            _ => Err(anyhow!(
+                "unknown rater group: {}, expected self, manager, peers or direct-reports",
+                s
+            )),
+        }
+    }
+}
+
+// Assigns the assessment kinds and discriminator values to the rater groups
+#[derive(Debug, Clone)]
+pub struct RaterConfig {
+    pub self_kind: String,
+
+    // Groups by title in lowercase, set with `--rater-group`. Override the default names.
+    pub groups: HashMap<String, RaterGroup>,
+}
+
+impl Default for RaterConfig {
+    fn default() -> Self {
+        RaterConfig {
+            self_kind: String::from("Self-Assessment"),
+            groups: HashMap::new(),
+        }
+    }
+}
+
+impl RaterConfig {
+    pub fn add(&mut self, input: &str) -> anyhow::Result<()> {
+        let mut parts = input.rsplitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(group), Some(title)) if !title.trim().is_empty() => {
+                self.groups
+                    .insert(title.trim().to_lowercase(), group.parse()?);
+                Ok(())
+            }
+            _ => bail!("invalid `rater-group`: {}, expected KIND=GROUP", input),
+        }
+    }
+
+    // The titles are matched as a whole, other titles are in no group
+    pub fn group(&self, title: &str) -> Option<RaterGroup> {
+        let title = title.trim().to_lowercase();
+        if let Some(group) = self.groups.get(&title) {
+            return Some(*group);
+        }
+
+        if title == self.self_kind.to_lowercase() {
+            return Some(RaterGroup::Myself);
+        }
+
+        DEFAULT_GROUPS
+            .iter()
+            .find(|(name, _)| *name == title)
+            .map(|(_, group)| *group)
+    }
+}
+
+impl Display for RaterGroup {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RaterGroup::Myself => write!(f, "Self"),
+            RaterGroup::Manager => write!(f, "Manager"),
+            RaterGroup::Peers => write!(f, "Peers"),
+            RaterGroup::DirectReports => write!(f, "Direct Reports"),
+            RaterGroup::AllOthers => write!(f, "All Others"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RaterGroupScore {
+    pub group: RaterGroup,
+    pub category: String,
+    pub score: Option<f32>,
+    // Number of raters behind the score
+    pub count: usize,
+}
+
+// Merges the graded responses by rater group and category.
+// Responses of unrecognized groups are only accounted in `All Others`.
+pub fn breakdown(grades: &[Responses], config: &RaterConfig) -> Vec<(RaterGroup, Responses)> {
+    let mut groups: Vec<(RaterGroup, Responses)> = Vec::new();
+
+    for responses in grades {
+        let group = config.group(&responses.assessment_kind);
+
+        let mut targets = Vec::with_capacity(2);
+        if let Some(group) = group {
+            targets.push(group);
+        }
+        if group != Some(RaterGroup::Myself) {
+            targets.push(RaterGroup::AllOthers);
+        }
+
+        for target in targets {
+            let pos = match groups.iter().position(|(group, r)| {
+                *group == target && r.category_name == responses.category_name
+            }) {
+                Some(pos) => pos,
+                None => {
+                    groups.push((
+                        target,
+                        Responses::new(
+                            responses.response_kind.clone(),
+                            target.to_string(),
+                            responses.category_name.clone(),
+                        ),
+                    ));
+                    groups.len() - 1
+                }
+            };
+
+            for answer in responses.read() {
+                groups[pos].1.write(Answer::clone(answer));
+            }
+        }
+    }
+
+    groups.sort_by_key(|(group, _)| *group);
+    groups
+}
+
+// The breakdown only adds information if the responses come from more than one non-self group
+pub fn has_breakdown(grades: &[Responses], config: &RaterConfig) -> bool {
+    let mut others: Vec<&str> = grades
+        .iter()
+        .filter(|r| config.group(&r.assessment_kind) != Some(RaterGroup::Myself))
+        .map(|r| r.assessment_kind.as_str())
+        .collect();
+
+    others.sort_unstable();
+    others.dedup();
+    others.len() > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_whole_names_only() {
+        let config = RaterConfig::default();
+
+        assert_eq!(config.group("Self-Assessment"), Some(RaterGroup::Myself));
+        assert_eq!(config.group(" team lead "), Some(RaterGroup::Manager));
+        assert_eq!(config.group("Peers"), Some(RaterGroup::Peers));
+        assert_eq!(
+            config.group("Direct Report"),
+            Some(RaterGroup::DirectReports)
+        );
+        assert_eq!(config.group("Leadership Circle"), None);
+        assert_eq!(config.group("Reporting Team"), None);
+        assert_eq!(config.group("Selfish"), None);
+    }
+
+    #[test]
+    fn mapped_names_take_precedence() {
+        let mut config = RaterConfig {
+            self_kind: String::from("Me"),
+            ..RaterConfig::default()
+        };
+        config.add("Leadership Circle=manager").unwrap();
+        config.add("Peer=direct-reports").unwrap();
+
+        assert_eq!(config.group("leadership circle"), Some(RaterGroup::Manager));
+        assert_eq!(config.group("Peer"), Some(RaterGroup::DirectReports));
+        assert_eq!(config.group("me"), Some(RaterGroup::Myself));
+
+        assert!(config.add("Leadership Circle").is_err());
+        assert!(config.add("=manager").is_err());
+        assert!(config.add("Leadership Circle=boss").is_err());
+    }
+
+    #[test]
+    fn breaks_the_grades_down_by_group() {
+        let config = RaterConfig::default();
+        let grades = ["Self-Assessment", "Manager", "Peer", "Stakeholder"]
+            .iter()
+            .map(|kind| {
+                let mut responses = Responses::new(
+                    crate::config::ResponseKind::Grade,
+                    kind.to_string(),
+                    String::from("Communication"),
+                );
+                responses.write(Answer {
+                    value: String::from("50"),
+                    weight: 1.0,
+                    grade: None,
+                    respondent: kind.to_string(),
+                });
+                responses
+            })
+            .collect::<Vec<Responses>>();
+
+        assert!(has_breakdown(&grades, &config));
+        let groups = breakdown(&grades, &config)
+            .into_iter()
+            .map(|(group, responses)| (group, responses.respondents()))
+            .collect::<Vec<(RaterGroup, usize)>>();
+        assert_eq!(
+            groups,
+            vec![
+                (RaterGroup::Myself, 1),
+                (RaterGroup::Manager, 1),
+                (RaterGroup::Peers, 1),
+                (RaterGroup::AllOthers, 3),
+            ]
+        );
+
+        assert!(!has_breakdown(&grades[..2], &config));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use serde_derive::Serialize;
+
+use crate::survey::Grade;
+
+// Descriptive statistics of the grades of a single category
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+
+    // Number of responses per (rounded) grade, as answered on the scale of the question
+    pub histogram: BTreeMap<i64, usize>,
+}
+
+impl Stats {
+    // The statistics are computed of the 0 to 100 scores, the histogram counts the answered grades
+    pub fn from_grades(grades: &[f32], answered: &[Grade]) -> Option<Self> {
+        if grades.is_empty() {
+            return None;
+        }
+
+        let mut sorted = grades.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let count = sorted.len();
+        let mean = sorted.iter().sum::<f32>() / count as f32;
+        let variance = sorted.iter().map(|g| (g - mean).powi(2)).sum::<f32>() / count as f32;
+
+        let median = if count % 2 == 1 {
+            sorted[count / 2]
+        } else {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+        };
+
+        // every grade of the question scales is listed, answered or not
+        let mut histogram: BTreeMap<i64, usize> = BTreeMap::new();
+        for grade in answered {
+            let (low, high) = grade.scale;
+            for score in low.round() as i64..=high.round() as i64 {
+                histogram.entry(score).or_insert(0);
+            }
+        }
+        for grade in answered {
+            *histogram.entry(grade.value.round() as i64).or_insert(0) += 1;
+        }
+
+        Some(Stats {
+            count,
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[count - 1],
+            histogram,
+        })
+    }
+
+    // Labeled values as displayed in the summary rows.
+    // `scores` lists the histogram buckets to display, so that all categories share the same rows.
+    pub fn rows(&self, scores: &[i64]) -> Vec<(String, String)> {
+        let mut rows = vec![
+            (String::from("count"), self.count.to_string()),
+            (String::from("mean"), format!("{:.1}", self.mean)),
+            (String::from("median"), format!("{:.1}", self.median)),
+            (String::from("std. dev."), format!("{:.2}", self.std_dev)),
+            (String::from("min"), format!("{:.1}", self.min)),
+            (String::from("max"), format!("{:.1}", self.max)),
+        ];
+
+        for score in scores {
+            let occurrences = self.histogram.get(score).cloned().unwrap_or(0);
+            rows.push((format!("score {}", score), occurrences.to_string()));
+        }
+
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grade(value: f32, scale: (f32, f32)) -> Grade {
+        Grade { value, scale }
+    }
+
+    #[test]
+    fn describes_the_scores() {
+        let stats = Stats::from_grades(&[25.0, 50.0, 100.0, 75.0], &[]).unwrap();
+
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.mean, 62.5);
+        assert_eq!(stats.median, 62.5);
+        assert_eq!(stats.min, 25.0);
+        assert_eq!(stats.max, 100.0);
+        assert!((stats.std_dev - 27.95085).abs() < 0.001);
+
+        assert_eq!(
+            Stats::from_grades(&[10.0, 30.0, 20.0], &[]).unwrap().median,
+            20.0
+        );
+        assert!(Stats::from_grades(&[], &[]).is_none());
+    }
+
+    #[test]
+    fn counts_the_grades_on_the_question_scales() {
+        let stats = Stats::from_grades(
+            &[0.0],
+            &[
+                grade(7.0, (1.0, 10.0)),
+                grade(7.0, (1.0, 10.0)),
+                grade(3.0, (0.0, 4.0)),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            stats.histogram.into_iter().collect::<Vec<(i64, usize)>>(),
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (3, 1),
+                (4, 0),
+                (5, 0),
+                (6, 0),
+                (7, 2),
+                (8, 0),
+                (9, 0),
+                (10, 0)
+            ]
+        );
+    }
+}
@@ -1,12 +1,35 @@
 use crate::config::ResponseKind;
-use crate::survey::Responses;
-use std::collections::HashMap;
+use crate::survey::anonymity::Suppression;
+use crate::survey::gap::{self, Gap, GapConfig};
+use crate::survey::rater::{self, RaterConfig, RaterGroupScore};
+use crate::survey::validation::Report;
+use crate::survey::{stats::Stats, Responses};
+use serde_derive::Serialize;
+use std::collections::{BTreeSet, HashMap};
 use std::ops::Deref;
 
+pub const GRADES_TITLE: &str = "Grades";
+pub const RATER_GROUPS_TITLE: &str = "360° View";
+pub const REVIEWS_TITLE: &str = "Reviews";
+pub const VALIDATION_TITLE: &str = "Validation";
+pub const CHOICES_TITLE: &str = "Choices";
+pub const CHECKBOXES_TITLE: &str = "Checkboxes";
+pub const NUMBERS_TITLE: &str = "Numbers";
+pub const NPS_TITLE: &str = "Net Promoter Score";
+
 #[derive(Debug, Default)]
 pub struct Summary {
     texts: Vec<Responses>,
     grades: Vec<Responses>,
+    choices: Vec<Responses>,
+    checkboxes: Vec<Responses>,
+    numbers: Vec<Responses>,
+    nps: Vec<Responses>,
+    show_unweighted: bool,
+    gap_config: GapConfig,
+    raters: RaterConfig,
+    validation: Report,
+    suppressions: Vec<Suppression>,
 }
 
 impl Summary {
@@ -19,21 +42,410 @@ impl Summary {
             ResponseKind::Grade => self.grades = v,
             ResponseKind::Text => self.texts = v,
             ResponseKind::Discriminator => {}
+            ResponseKind::Choice => self.choices = v,
+            ResponseKind::Checkbox => self.checkboxes = v,
+            ResponseKind::Numeric => self.numbers = v,
+            ResponseKind::Nps => self.nps = v,
+        }
+    }
+
+    // Adds the unweighted mean next to the weighted one for every graded category
+    pub fn set_show_unweighted(&mut self, show_unweighted: bool) {
+        self.show_unweighted = show_unweighted;
+    }
+
+    // The validation report is emitted along with the summary
+    pub fn set_validation(&mut self, validation: Report) {
+        self.validation = validation;
+    }
+
+    // Groups of responses that were not published to keep the respondents anonymous
+    pub fn set_suppressions(&mut self, suppressions: Vec<Suppression>) {
+        self.suppressions = suppressions;
+    }
+
+    pub fn set_gap_config(&mut self, gap_config: GapConfig) {
+        self.gap_config = gap_config;
+    }
+
+    pub fn set_rater_config(&mut self, raters: RaterConfig) {
+        self.raters = raters;
+    }
+
+    // Text answers by category and assessment kind
+    pub fn texts(&self) -> &[Responses] {
+        &self.texts
+    }
+
+    pub fn gap_analysis(&self) -> Vec<Gap> {
+        gap::analyze(&self.grades, &self.gap_config)
+    }
+
+    // The 360° view is shown if the responses come from more than one group of raters
+    fn has_rater_groups(&self) -> bool {
+        rater::has_breakdown(&self.grades, &self.raters)
+    }
+
+    pub fn rater_groups(&self) -> Vec<RaterGroupScore> {
+        if !self.has_rater_groups() {
+            return Vec::new();
+        }
+
+        rater::breakdown(&self.grades, &self.raters)
+            .into_iter()
+            .map(|(group, responses)| RaterGroupScore {
+                group,
+                category: responses.category_name.clone(),
+                score: responses.weighted_mean(),
+                count: responses.respondents(),
+            })
+            .collect()
+    }
+
+    // Title of the charted table and the number of series following its header row.
+    // The 360° view is charted if available, otherwise the grades of every assessment kind.
+    pub fn chart_table(&self) -> Option<(&'static str, usize)> {
+        if self.has_rater_groups() {
+            let rows = self
+                .generate_rows()
+                .into_iter()
+                .find(|rows| rows.title() == RATER_GROUPS_TITLE)?;
+
+            return Some((RATER_GROUPS_TITLE, rows.rows().len().saturating_sub(1)));
+        }
+
+        // the unweighted rows of the grades are not charted
+        let mut kinds: Vec<&str> = self
+            .grades
+            .iter()
+            .map(|r| r.assessment_kind.as_str())
+            .collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+
+        if kinds.is_empty() {
+            None
+        } else {
+            Some((GRADES_TITLE, kinds.len()))
         }
     }
 
-    pub fn generate_rows(self) -> Vec<SummaryRows> {
+    // Scores of the charted table for the locally rendered charts
+    pub fn chart_series(&self) -> ChartSeries {
+        let scores: Vec<(String, String, Option<f32>)> = if self.has_rater_groups() {
+            self.rater_groups()
+                .into_iter()
+                .map(|score| (score.group.to_string(), score.category, score.score))
+                .collect()
+        } else {
+            self.grades
+                .iter()
+                .map(|r| {
+                    (
+                        r.assessment_kind.clone(),
+                        r.category_name.clone(),
+                        r.weighted_mean(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut chart = ChartSeries::default();
+        for (series, category, score) in scores {
+            chart.add(&series, &category, score);
+        }
+
+        chart
+    }
+
+    pub fn generate_rows(&self) -> Vec<SummaryRows> {
         let mut all_rows = Vec::with_capacity(2);
         let response_kinds = [ResponseKind::Grade, ResponseKind::Text].iter();
 
-        for (response_kind, data) in response_kinds.zip([self.grades, self.texts].iter()) {
+        for (response_kind, data) in response_kinds.zip([&self.grades, &self.texts].iter()) {
+            if let Some(mut rows) = generate_summary_rows(response_kind, data) {
+                if *response_kind == ResponseKind::Grade && self.show_unweighted {
+                    add_unweighted_rows(&mut rows, data);
+                }
+                all_rows.push(rows);
+            }
+
+            if *response_kind == ResponseKind::Grade && self.has_rater_groups() {
+                all_rows.push(generate_rater_group_rows(&self.grades, &self.raters));
+            }
+        }
+
+        // the other kinds have a section only if they were asked
+        let other_kinds = [
+            ResponseKind::Choice,
+            ResponseKind::Checkbox,
+            ResponseKind::Numeric,
+            ResponseKind::Nps,
+        ];
+        let other_data = [&self.choices, &self.checkboxes, &self.numbers, &self.nps];
+        for (response_kind, data) in other_kinds.iter().zip(other_data.iter()) {
+            if data.is_empty() {
+                continue;
+            }
             if let Some(rows) = generate_summary_rows(response_kind, data) {
                 all_rows.push(rows);
             }
         }
 
+        if !self.grades.is_empty() {
+            all_rows.push(generate_stats_rows(&self.grades));
+        }
+
+        let gaps = self.gap_analysis();
+        if !gaps.is_empty() {
+            all_rows.push(generate_gap_rows(&gaps, &self.gap_config));
+        }
+
+        if !self.suppressions.is_empty() {
+            all_rows.push(generate_suppression_rows(&self.suppressions));
+        }
+
+        if !self.validation.is_empty() {
+            all_rows.push(generate_validation_rows(&self.validation));
+        }
+
         all_rows
     }
+
+    pub fn document(&self) -> SummaryDocument {
+        let mut grade = summary_entries(&ResponseKind::Grade, &self.grades);
+        for (entry, category) in grade.iter_mut().zip(self.grades.iter()) {
+            if self.show_unweighted {
+                entry.unweighted_result = ResponseKind::Grade.process_grades_unweighted(category);
+            }
+            entry.stats = category.stats();
+        }
+
+        SummaryDocument {
+            grade,
+            text: summary_entries(&ResponseKind::Text, &self.texts),
+            choice: summary_entries(&ResponseKind::Choice, &self.choices),
+            checkbox: summary_entries(&ResponseKind::Checkbox, &self.checkboxes),
+            numeric: summary_entries(&ResponseKind::Numeric, &self.numbers),
+            nps: summary_entries(&ResponseKind::Nps, &self.nps),
+            rater_groups: self.rater_groups(),
+            gap_analysis: self.gap_analysis(),
+            suppressed: self.suppressions.clone(),
+            validation: self.validation.clone(),
+        }
+    }
+}
+
+// Categories and the scores of every series by category, in the order of the summary
+#[derive(Debug, Default)]
+pub struct ChartSeries {
+    pub categories: Vec<String>,
+    pub series: Vec<(String, Vec<Option<f32>>)>,
+}
+
+impl ChartSeries {
+    fn add(&mut self, series: &str, category: &str, score: Option<f32>) {
+        let category_pos = match self.categories.iter().position(|c| c == category) {
+            Some(pos) => pos,
+            None => {
+                self.categories.push(category.to_owned());
+                for (_, scores) in self.series.iter_mut() {
+                    scores.push(None);
+                }
+                self.categories.len() - 1
+            }
+        };
+
+        let series_pos = match self.series.iter().position(|(title, _)| title == series) {
+            Some(pos) => pos,
+            None => {
+                self.series
+                    .push((series.to_owned(), vec![None; self.categories.len()]));
+                self.series.len() - 1
+            }
+        };
+
+        self.series[series_pos].1[category_pos] = score;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+// Structured representation of the summary, used by the machine readable outputs
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryDocument {
+    pub grade: Vec<SummaryEntry>,
+    pub text: Vec<SummaryEntry>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub choice: Vec<SummaryEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checkbox: Vec<SummaryEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub numeric: Vec<SummaryEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nps: Vec<SummaryEntry>,
+
+    pub rater_groups: Vec<RaterGroupScore>,
+    pub gap_analysis: Vec<Gap>,
+    pub suppressed: Vec<Suppression>,
+    pub validation: Report,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryEntry {
+    pub assessment_kind: String,
+    pub category: String,
+    pub result: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unweighted_result: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Stats>,
+}
+
+fn summary_entries(response_kind: &ResponseKind, data: &[Responses]) -> Vec<SummaryEntry> {
+    data.iter()
+        .map(|category| SummaryEntry {
+            assessment_kind: category.assessment_kind.clone(),
+            category: category.category_name.clone(),
+            result: response_kind.process_data(category),
+            unweighted_result: None,
+            stats: None,
+        })
+        .collect()
+}
+
+// Descriptive statistics per category, one row per statistic and assessment kind
+fn generate_stats_rows(by_category: &[Responses]) -> SummaryRows {
+    let stats = by_category
+        .iter()
+        .map(|category| category.stats())
+        .collect::<Vec<Option<Stats>>>();
+
+    let scores = stats
+        .iter()
+        .flatten()
+        .flat_map(|category_stats| category_stats.histogram.keys().cloned())
+        .collect::<BTreeSet<i64>>()
+        .into_iter()
+        .collect::<Vec<i64>>();
+
+    let mut rows = SummaryRows::new("Statistics");
+    for (category, category_stats) in by_category.iter().zip(stats.iter()) {
+        rows.add_header(HEADER_KEY, &category.category_name);
+
+        if let Some(category_stats) = category_stats {
+            for (label, v) in category_stats.rows(&scores) {
+                rows.set_cell(
+                    &format!("{} {}", category.assessment_kind, label),
+                    &category.category_name,
+                    &v,
+                );
+            }
+        }
+    }
+
+    rows
+}
+
+// The unweighted rows are appended after the weighted ones,
+// so that the rows referenced by the chart stay the same
+fn add_unweighted_rows(rows: &mut SummaryRows, by_category: &[Responses]) {
+    for category in by_category {
+        rows.set_cell(
+            format!("{} (unweighted)", category.assessment_kind).as_ref(),
+            &category.category_name,
+            ResponseKind::Grade
+                .process_grades_unweighted(category)
+                .unwrap_or_default()
+                .as_ref(),
+        );
+    }
+}
+
+// Per category scores of every rater group
+fn generate_rater_group_rows(grades: &[Responses], raters: &RaterConfig) -> SummaryRows {
+    let groups = rater::breakdown(grades, raters)
+        .into_iter()
+        .map(|(_, responses)| responses)
+        .collect::<Vec<Responses>>();
+
+    let assessment_kind = |r: &Responses| -> String { r.assessment_kind.clone() };
+    let category_name = |r: &Responses| -> String { r.category_name.clone() };
+
+    fill_summary_rows(
+        RATER_GROUPS_TITLE,
+        &ResponseKind::Grade,
+        &groups,
+        Box::new(category_name),
+        Box::new(assessment_kind),
+    )
+}
+
+// Self vs. team scores per category, along with the delta and the gap kind
+fn generate_gap_rows(gaps: &[Gap], gap_config: &GapConfig) -> SummaryRows {
+    let mut rows = SummaryRows::new("Gap Analysis");
+    for gap in gaps {
+        rows.add_header(HEADER_KEY, &gap.category);
+        rows.add_cell(&gap_config.self_kind, &format!("{:.1}", gap.self_score));
+        rows.add_cell(&gap_config.team_kind, &format!("{:.1}", gap.team_score));
+        rows.add_cell("Delta", &format!("{:+.1}", gap.delta));
+        rows.add_cell("Gap", &gap.kind.to_string());
+    }
+
+    rows
+}
+
+// One row per group of responses suppressed for anonymity
+fn generate_suppression_rows(suppressions: &[Suppression]) -> SummaryRows {
+    let mut rows = SummaryRows::new("Anonymity");
+    for header in [
+        "Response Kind",
+        "Assessment Kind",
+        "Category",
+        "Respondents",
+        "Suppressed",
+    ]
+    .iter()
+    {
+        rows.add_header(HEADER_KEY, header);
+    }
+
+    for (i, suppression) in suppressions.iter().enumerate() {
+        let key = format!("#{}", i + 1);
+        rows.add_cell(&key, &suppression.response_kind.to_string());
+        rows.add_cell(&key, &suppression.assessment_kind);
+        rows.add_cell(&key, &suppression.category);
+        rows.add_cell(&key, &suppression.respondents.to_string());
+        rows.add_cell(&key, &suppression.kind.to_string());
+    }
+
+    rows
+}
+
+// One row per skipped cell
+fn generate_validation_rows(validation: &Report) -> SummaryRows {
+    let mut rows = SummaryRows::new(VALIDATION_TITLE);
+    for header in ["Sheet", "Row", "Column", "Issue", "Value"].iter() {
+        rows.add_header(HEADER_KEY, header);
+    }
+
+    for (i, issue) in validation.issues.iter().enumerate() {
+        let key = format!("#{}", i + 1);
+        rows.add_cell(&key, &issue.sheet);
+        rows.add_cell(&key, &issue.row.to_string());
+        rows.add_cell(&key, &issue.column);
+        rows.add_cell(&key, &issue.kind.to_string());
+        rows.add_cell(&key, &issue.value);
+    }
+
+    rows
 }
 
 fn generate_summary_rows(response_kind: &ResponseKind, data: &[Responses]) -> Option<SummaryRows> {
@@ -42,36 +454,59 @@ fn generate_summary_rows(response_kind: &ResponseKind, data: &[Responses]) -> Op
 
     match response_kind {
         ResponseKind::Grade => Some(fill_summary_rows(
+            GRADES_TITLE,
             response_kind,
             data,
             Box::new(category_name),
             Box::new(assessment_kind),
         )),
         ResponseKind::Text => Some(fill_summary_rows(
+            REVIEWS_TITLE,
             response_kind,
             data,
             Box::new(assessment_kind),
             Box::new(category_name),
         )),
         ResponseKind::Discriminator => None,
+        ResponseKind::Choice => Some(fill_other_rows(CHOICES_TITLE, response_kind, data)),
+        ResponseKind::Checkbox => Some(fill_other_rows(CHECKBOXES_TITLE, response_kind, data)),
+        ResponseKind::Numeric => Some(fill_other_rows(NUMBERS_TITLE, response_kind, data)),
+        ResponseKind::Nps => Some(fill_other_rows(NPS_TITLE, response_kind, data)),
     }
 }
 
+// One column per category, one row per assessment kind, as the grades
+fn fill_other_rows(title: &str, response_kind: &ResponseKind, data: &[Responses]) -> SummaryRows {
+    let assessment_kind = |r: &Responses| -> String { r.assessment_kind.clone() };
+    let category_name = |r: &Responses| -> String { r.category_name.clone() };
+
+    fill_summary_rows(
+        title,
+        response_kind,
+        data,
+        Box::new(category_name),
+        Box::new(assessment_kind),
+    )
+}
+
 fn fill_summary_rows(
+    title: &str,
     response_kind: &ResponseKind,
     by_category: &[Responses],
     header: Box<dyn Fn(&Responses) -> String>,
     cell_key: Box<dyn Fn(&Responses) -> String>,
 ) -> SummaryRows {
-    let mut rows = SummaryRows::new();
+    let mut rows = SummaryRows::new(title);
     for category in by_category {
-        rows.add_header("Data", header(&category).as_ref());
+        let column = header(&category);
+        rows.add_header(HEADER_KEY, &column);
 
-        rows.add_cell(
+        rows.set_cell(
             cell_key(&category).as_ref(),
+            &column,
             response_kind
-                .process_data(category.read())
-                .unwrap() // todo: check unwrap
+                .process_data(category)
+                .unwrap_or_default()
                 .as_ref(),
         );
     }
@@ -79,19 +514,28 @@ fn fill_summary_rows(
     rows
 }
 
+// Key of the header row of every table
+const HEADER_KEY: &str = "Data";
+
 pub struct SummaryRows {
+    title: String,
     base: HashMap<String, Vec<String>>,
     ordered_keys: Vec<String>,
 }
 
 impl SummaryRows {
-    fn new() -> Self {
+    fn new(title: &str) -> Self {
         SummaryRows {
+            title: String::from(title),
             base: HashMap::new(),
             ordered_keys: Vec::new(),
         }
     }
 
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
     fn add_header(&mut self, group_key: &str, v: &str) {
         if self.unique_entry_exists(group_key, v).is_none() {
             self.add_cell(group_key, v);
@@ -110,6 +554,29 @@ impl SummaryRows {
         };
     }
 
+    // Puts the value under its header, the columns of missing or withheld groups stay blank.
+    // The header has to be added first.
+    fn set_cell(&mut self, group_key: &str, header: &str, v: &str) {
+        let column = match self
+            .cell_entry_exists(HEADER_KEY)
+            .and_then(|headers| headers.iter().skip(1).position(|name| name == header))
+        {
+            Some(column) => column + 1,
+            None => return self.add_cell(group_key, v),
+        };
+
+        if self.cell_entry_exists(group_key).is_none() {
+            self.insert(group_key, "");
+        }
+
+        if let Some(row) = self.base.get_mut(group_key) {
+            if row.len() <= column {
+                row.resize(column + 1, String::new());
+            }
+            row[column] = String::from(v);
+        }
+    }
+
     // as opposed to cell_entry_exists this function allows
     // to have only unique header values
     fn unique_entry_exists(&self, group_key: &str, v: &str) -> Option<&String> {
@@ -139,15 +606,90 @@ impl SummaryRows {
         self.ordered_keys.push(String::from(group_key));
     }
 
+    // Every row has a cell per header, the rows missing the trailing groups are padded
     pub fn rows(&self) -> Vec<Vec<String>> {
+        let width = self.base.get(HEADER_KEY).map_or(0, Vec::len);
+
         let mut out: Vec<Vec<String>> = Vec::new();
         for key in &self.ordered_keys {
             let key_str: &str = key.as_ref();
             if let Some(v) = self.base.get(key_str) {
-                out.push(v.deref().to_vec());
+                let mut row = v.deref().to_vec();
+                if row.len() < width {
+                    row.resize(width, String::new());
+                }
+                out.push(row);
             }
         }
 
         out
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::survey::rater::RaterGroup;
+    use crate::survey::Answer;
+
+    #[test]
+    fn cells_stay_under_their_headers() {
+        let mut rows = SummaryRows::new(GRADES_TITLE);
+        for header in ["Adaptability", "Attitude", "Communication"].iter() {
+            rows.add_header(HEADER_KEY, header);
+        }
+        // the Team-Feedback group of Adaptability is withheld
+        rows.set_cell("Team-Feedback", "Attitude", "80.0");
+        rows.set_cell("Team-Feedback", "Communication", "60.0");
+        rows.set_cell("Self-Assessment", "Adaptability", "75.0");
+
+        assert_eq!(
+            rows.rows(),
+            vec![
+                vec!["Data", "Adaptability", "Attitude", "Communication"],
+                vec!["Team-Feedback", "", "80.0", "60.0"],
+                vec!["Self-Assessment", "75.0", "", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn rater_groups_count_the_raters() {
+        let mut grades = Vec::new();
+        for (kind, respondents) in
+            [("Manager", &["m", "m"][..]), ("Peer", &["a", "b", "a"][..])].iter()
+        {
+            let mut responses = Responses::new(
+                ResponseKind::Grade,
+                kind.to_string(),
+                String::from("Communication"),
+            );
+            for respondent in respondents.iter() {
+                responses.write(Answer {
+                    value: String::from("50"),
+                    weight: 1.0,
+                    grade: None,
+                    respondent: respondent.to_string(),
+                });
+            }
+            grades.push(responses);
+        }
+
+        let mut summary = Summary::new();
+        summary.set_by_kind(&ResponseKind::Grade, grades);
+
+        let counts = summary
+            .rater_groups()
+            .into_iter()
+            .map(|score| (score.group, score.count))
+            .collect::<Vec<(RaterGroup, usize)>>();
+        assert_eq!(
+            counts,
+            vec![
+                (RaterGroup::Manager, 1),
+                (RaterGroup::Peers, 2),
+                (RaterGroup::AllOthers, 3)
+            ]
+        );
+    }
+}
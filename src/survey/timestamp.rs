@@ -0,0 +1,310 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::sheets::spreadsheets_values::SpreadsheetValueRange;
+use crate::survey::{pad_columns, sheet_title, TIMESTAMP_COLUMN};
+
+// Submission time of a response, as recorded by Google Forms in the `Timestamp` column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp {
+    year: u32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+// Order of the date components, which depends on the spreadsheet locale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateOrder {
+    // `2019-01-31` is always year first, `31.01.2019` day first,
+    // `1/31/2019` month first unless the first component can't be a month
+    Auto,
+    // `1/31/2019`, i.e. en_US
+    MonthFirst,
+    // `31/01/2019`, i.e. en_GB, de_DE
+    DayFirst,
+}
+
+impl FromStr for DateOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(DateOrder::Auto),
+            "mdy" => Ok(DateOrder::MonthFirst),
+            "dmy" => Ok(DateOrder::DayFirst),
+            _ => Err(anyhow!("unknown date order: {}", s)),
+        }
+    }
+}
+
+// Parses the timestamps of the sheets and the csv exports, i.e. `2019-01-01 09:51:34`,
+// the Forms default `1/01/2019 9:51:34`, `01.01.2019 09:51` or `1/01/2019 9:51:34 AM`.
+// Date only input is the start of the day.
+pub fn parse(input: &str, order: DateOrder) -> Option<Timestamp> {
+    let mut parts = input.trim().splitn(2, &[' ', 'T'][..]);
+    let date = parts.next()?;
+    let time = parts.next().unwrap_or("").trim();
+
+    let dotted = date.contains('.');
+    let date = numbers(date, &['-', '/', '.'][..])?;
+    let (year, month, day) = match (date.as_slice(), order) {
+        ([year, month, day], _) if *year > 999 => (*year, *month, *day),
+        ([day, month, year], DateOrder::DayFirst) => (*year, *month, *day),
+        ([day, month, year], DateOrder::Auto) if dotted || *day > 12 => (*year, *month, *day),
+        ([month, day, year], _) => (*year, *month, *day),
+        _ => return None,
+    };
+
+    // 12-hour clock of the en_US locale
+    let upper = time.to_uppercase();
+    let (time, offset) = if let Some(time) = upper.strip_suffix("AM") {
+        (time.trim(), Some(0))
+    } else if let Some(time) = upper.strip_suffix("PM") {
+        (time.trim(), Some(12))
+    } else {
+        (time, None)
+    };
+
+    let time = if time.is_empty() {
+        vec![0, 0, 0]
+    } else {
+        numbers(time, &[':'][..])?
+    };
+    let (mut hour, minute, second) = match time.as_slice() {
+        [hour, minute] => (*hour, *minute, 0),
+        [hour, minute, second] => (*hour, *minute, *second),
+        _ => return None,
+    };
+
+    if let Some(offset) = offset {
+        if hour == 0 || hour > 12 {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+
+    if month == 0 || month > 12 || day == 0 || day > 31 || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    Some(Timestamp {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+    })
+}
+
+fn numbers(input: &str, separators: &[char]) -> Option<Vec<u32>> {
+    input
+        .split(separators)
+        .map(|n| n.trim().parse::<u32>().ok())
+        .collect()
+}
+
+// Review window of the evaluation, both bounds are inclusive
+#[derive(Debug, Clone, Copy)]
+pub struct TimeWindow {
+    pub since: Option<Timestamp>,
+    pub until: Option<Timestamp>,
+    pub date_order: DateOrder,
+}
+
+impl Default for TimeWindow {
+    fn default() -> Self {
+        TimeWindow {
+            since: None,
+            until: None,
+            date_order: DateOrder::Auto,
+        }
+    }
+}
+
+impl TimeWindow {
+    pub fn set_since(&mut self, input: &str) -> anyhow::Result<()> {
+        self.since = Some(parse_bound(input, "since")?);
+        Ok(())
+    }
+
+    // A date only bound covers the whole day
+    pub fn set_until(&mut self, input: &str) -> anyhow::Result<()> {
+        let mut until = parse_bound(input, "until")?;
+        if !input.trim().contains(':') {
+            until.hour = 23;
+            until.minute = 59;
+            until.second = 59;
+        }
+
+        self.until = Some(until);
+        Ok(())
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        !matches!(self.since, Some(since) if timestamp < since)
+            && !matches!(self.until, Some(until) if timestamp > until)
+    }
+}
+
+fn parse_bound(input: &str, name: &str) -> anyhow::Result<Timestamp> {
+    parse(input, DateOrder::Auto).ok_or_else(|| {
+        anyhow!(
+            "invalid `{}`: {}, expected YYYY-MM-DD or YYYY-MM-DD HH:MM:SS",
+            name,
+            input
+        )
+    })
+}
+
+// Number of responses outside of the window per sheet
+#[derive(Debug, Clone, Default)]
+pub struct WindowReport {
+    pub excluded: Vec<(String, usize)>,
+
+    // Responses excluded because their timestamp could not be parsed
+    pub unparsable: usize,
+}
+
+impl WindowReport {
+    pub fn print(&self) {
+        let total: usize = self.excluded.iter().map(|(_, excluded)| excluded).sum();
+        println!(
+            "> time window: {} response(s) outside of the window excluded",
+            total
+        );
+
+        for (sheet, excluded) in self.excluded.iter().filter(|(_, excluded)| *excluded > 0) {
+            println!(">   {}: {}", sheet, excluded);
+        }
+        if self.unparsable > 0 {
+            eprintln!(
+                ">   {} of them with an unparsable timestamp, try `--date-order`",
+                self.unparsable
+            );
+        }
+    }
+}
+
+// Removes the responses submitted outside of the window
+pub fn filter(sheets: &mut [SpreadsheetValueRange], window: &TimeWindow) -> WindowReport {
+    let mut report = WindowReport::default();
+
+    for sheet in sheets.iter_mut() {
+        let respondents = pad_columns(sheet);
+
+        let mut keep = vec![true; respondents + 1];
+        if let Some(timestamps) = sheet.values.get(TIMESTAMP_COLUMN) {
+            for (row, input) in timestamps.iter().enumerate().skip(1) {
+                keep[row] = match parse(input, window.date_order) {
+                    Some(timestamp) => window.contains(timestamp),
+                    None => {
+                        report.unparsable += 1;
+                        false
+                    }
+                };
+            }
+        }
+
+        let excluded = keep.iter().filter(|keep| !**keep).count();
+        report
+            .excluded
+            .push((sheet_title(&sheet.range).to_owned(), excluded));
+
+        for column in sheet.values.iter_mut() {
+            let mut row = 0;
+            column.retain(|_| {
+                row += 1;
+                keep[row - 1]
+            });
+        }
+
+        if sheet.rows.len() == respondents {
+            let mut row = 0;
+            sheet.rows.retain(|_| {
+                row += 1;
+                keep[row]
+            });
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: u32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> Timestamp {
+        Timestamp {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    #[test]
+    fn parses_the_forms_locale_formats() {
+        let cases = [
+            ("1/01/2019 9:51:34", at(2019, 1, 1, 9, 51, 34)),
+            ("1/31/2019 9:51:34", at(2019, 1, 31, 9, 51, 34)),
+            ("31/01/2019 09:51:34", at(2019, 1, 31, 9, 51, 34)),
+            ("01.02.2019 09:51", at(2019, 2, 1, 9, 51, 0)),
+            ("2019-01-31 09:51:34", at(2019, 1, 31, 9, 51, 34)),
+            ("2019-01-31T09:51:34", at(2019, 1, 31, 9, 51, 34)),
+            ("1/31/2019 9:51:34 PM", at(2019, 1, 31, 21, 51, 34)),
+            ("1/31/2019 12:05:00 am", at(2019, 1, 31, 0, 5, 0)),
+            ("1/31/2019 12:05:00 PM", at(2019, 1, 31, 12, 5, 0)),
+            ("2019-01-31", at(2019, 1, 31, 0, 0, 0)),
+        ];
+
+        for (input, expected) in cases.iter() {
+            assert_eq!(parse(input, DateOrder::Auto), Some(*expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn ambiguous_dates_follow_the_date_order() {
+        assert_eq!(
+            parse("1/02/2019 9:51:34", DateOrder::Auto),
+            Some(at(2019, 1, 2, 9, 51, 34))
+        );
+        assert_eq!(
+            parse("1/02/2019 9:51:34", DateOrder::MonthFirst),
+            Some(at(2019, 1, 2, 9, 51, 34))
+        );
+        assert_eq!(
+            parse("1/02/2019 9:51:34", DateOrder::DayFirst),
+            Some(at(2019, 2, 1, 9, 51, 34))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        for input in [
+            "",
+            "yesterday",
+            "13/13/2019 9:51:34",
+            "1/01/2019 24:00:00",
+            "1/01/2019 9:60:00",
+            "1/01/2019 13:00:00 PM",
+            "1/01/2019 0:10:00 AM",
+            "1/01 9:51:34",
+        ]
+        .iter()
+        {
+            assert_eq!(parse(input, DateOrder::Auto), None, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde_derive::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    // The answer can not be parsed according to the question's response kind
+    Unparsable,
+    // The grade is outside of the question's scale
+    OutOfRange,
+    // The choice is not one of the question's options
+    UnknownOption,
+    // No question template matches the column header
+    UnmatchedQuestion,
+    EmptyAnswer,
+}
+
+impl Display for IssueKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            IssueKind::Unparsable => write!(f, "unparsable"),
+            IssueKind::OutOfRange => write!(f, "out of range"),
+            IssueKind::UnknownOption => write!(f, "unknown option"),
+            IssueKind::UnmatchedQuestion => write!(f, "unmatched question"),
+            IssueKind::EmptyAnswer => write!(f, "empty answer"),
+        }
+    }
+}
+
+// A single invalid cell of the response sheets
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    pub sheet: String,
+
+    // 1-based row number, as displayed in the spreadsheet
+    pub row: usize,
+
+    // Column name, as displayed in the spreadsheet, i.e. `C`
+    pub column: String,
+    pub kind: IssueKind,
+    pub value: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}!{}{}: {}: {:?}",
+            self.sheet, self.column, self.row, self.kind, self.value
+        )
+    }
+}
+
+// Collects the cells skipped while scanning the responses
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn add(&mut self, sheet: &str, row: usize, column: usize, kind: IssueKind, value: &str) {
+        self.issues.push(Issue {
+            sheet: sheet.to_owned(),
+            row,
+            column: column_name(column),
+            kind,
+            value: value.to_owned(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.kind == kind)
+            .count()
+    }
+
+    pub fn print(&self) {
+        if self.is_empty() {
+            println!("> validation: no issues found");
+            return;
+        }
+
+        eprintln!("> validation: {} issue(s) found", self.issues.len());
+        for kind in [
+            IssueKind::Unparsable,
+            IssueKind::OutOfRange,
+            IssueKind::UnknownOption,
+            IssueKind::UnmatchedQuestion,
+            IssueKind::EmptyAnswer,
+        ]
+        .iter()
+        {
+            eprintln!(">   {}: {}", kind, self.count(*kind));
+        }
+
+        for issue in &self.issues {
+            eprintln!(">   {}", issue);
+        }
+    }
+}
+
+// Converts a 0-based column index into the spreadsheet column name: 0 -> A, 26 -> AA
+fn column_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut n = index + 1;
+
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.push((b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+
+    name.iter().rev().collect()
+}